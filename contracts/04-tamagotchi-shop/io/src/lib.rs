@@ -3,7 +3,12 @@
 use codec::{Decode, Encode};
 use gmeta::{In, InOut, Metadata, Out};
 #[allow(unused_imports)]
-use gstd::{exec, msg, prelude::*, ActorId};
+use gstd::{
+    collections::{BTreeMap, BTreeSet},
+    exec, msg,
+    prelude::*,
+    ActorId, MessageId,
+};
 use scale_info::TypeInfo;
 use sharded_fungible_token_io::*;
 use store_io::*;
@@ -26,6 +31,30 @@ pub struct Tamagotchi {
     pub ft_contract_id: ActorId,
     pub transaction_id: u64,
     pub approve_transaction: Option<(TransactionId, ActorId, u128)>,
+    /// The message waiting for the approval of `approve_transaction`.
+    pub approval_message: Option<MessageId>,
+    /// Store, attribute and price of an `ApproveAndBuy` that hasn't completed yet.
+    pub pending_purchase: Option<(ActorId, AttributeId, u128)>,
+    pub equipment: BTreeMap<EquipmentSlot, EquippedAttribute>,
+}
+
+#[derive(Encode, Decode, TypeInfo, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub enum EquipmentSlot {
+    Head,
+    Body,
+    Weapon,
+    Shield,
+    Accessory,
+}
+
+#[derive(Encode, Decode, TypeInfo, Clone, Copy, Debug, PartialEq, Eq)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub struct EquippedAttribute {
+    pub store_id: ActorId,
+    pub attribute_id: AttributeId,
 }
 
 #[derive(Encode, Decode, TypeInfo)]
@@ -50,10 +79,40 @@ pub enum TmgAction {
         store_id: ActorId,
         attribute_id: AttributeId,
     },
+    BuyBundle {
+        store_id: ActorId,
+        bundle_id: BundleId,
+    },
+    ApproveAndBuy {
+        store_id: ActorId,
+        attribute_id: AttributeId,
+    },
+    Equip {
+        store_id: ActorId,
+        attribute_id: AttributeId,
+        slot: EquipmentSlot,
+    },
+    Unequip {
+        slot: EquipmentSlot,
+    },
+    TransferAttribute {
+        store_id: ActorId,
+        attribute_id: AttributeId,
+        to: ActorId,
+    },
+    GiftAttribute {
+        store_id: ActorId,
+        attribute_id: AttributeId,
+        to: ActorId,
+    },
+    /// Sent by a store when another Tamagotchi transfers or gifts an
+    /// attribute to this one.
+    AttributeReceived {
+        attribute_id: AttributeId,
+        from: ActorId,
+    },
 }
 
-
-
 #[derive(Encode, Decode, TypeInfo)]
 #[codec(crate = gstd::codec)]
 #[scale_info(crate = gstd::scale_info)]
@@ -68,34 +127,245 @@ pub enum TmgEvent {
     Approved(ActorId),
     ApprovalRevoked,
     FTokenContractSet,
-    TokensApproved { account: ActorId, amount: u128 },
+    TokensApproved {
+        account: ActorId,
+        amount: u128,
+    },
     ApprovalError,
     AttributeBought(AttributeId),
     CompletePrevPurchase(AttributeId),
     ErrorDuringPurchase,
+    BundleBought(BundleId),
+    Equipped {
+        slot: EquipmentSlot,
+        attribute_id: AttributeId,
+    },
+    Unequipped(EquipmentSlot),
+    AttributeNotOwned(AttributeId),
+    AttributeEquipped(AttributeId),
+    AttributeTransferred {
+        attribute_id: AttributeId,
+        to: ActorId,
+    },
+    AttributeGifted {
+        attribute_id: AttributeId,
+        to: ActorId,
+    },
+    AttributeReceived {
+        store_id: ActorId,
+        attribute_id: AttributeId,
+        from: ActorId,
+    },
 }
 
 impl Tamagotchi {
-    pub async fn approve_tokens(&mut self, account: ActorId, amount: u128) {
-        let (transaction_id, account, amount) = if let Some((
-            prev_transaction_id,
-            prev_account,
-            prev_amount,
-        )) = self.approve_transaction
+    pub fn is_owner_or_approved(&self, user: &ActorId) -> bool {
+        self.owner == *user || self.approved_account == Some(*user)
+    }
+
+    /// Puts an attribute the Tamagotchi owns in `store_id` into `slot`,
+    /// replacing whatever was equipped there.
+    pub async fn equip(
+        &mut self,
+        store_id: ActorId,
+        attribute_id: AttributeId,
+        slot: EquipmentSlot,
+    ) {
+        if !get_attributes(store_id).await.contains(&attribute_id) {
+            msg::reply(TmgEvent::AttributeNotOwned(attribute_id), 0)
+                .expect("Error in sending a reply `TmgEvent::AttributeNotOwned`");
+            return;
+        }
+
+        let equipped = EquippedAttribute {
+            store_id,
+            attribute_id,
+        };
+        assert!(
+            self.equipment
+                .iter()
+                .all(|(equipped_slot, attribute)| *equipped_slot == slot || *attribute != equipped),
+            "The attribute is already equipped in another slot"
+        );
+        self.equipment.insert(slot, equipped);
+
+        msg::reply(TmgEvent::Equipped { slot, attribute_id }, 0)
+            .expect("Error in sending a reply `TmgEvent::Equipped`");
+    }
+
+    pub fn unequip(&mut self, slot: EquipmentSlot) {
+        assert!(
+            self.equipment.remove(&slot).is_some(),
+            "Nothing is equipped in that slot"
+        );
+
+        msg::reply(TmgEvent::Unequipped(slot), 0)
+            .expect("Error in sending a reply `TmgEvent::Unequipped`");
+    }
+
+    /// Gives an attribute bought in `store_id` to another Tamagotchi.
+    /// An equipped attribute must be unequipped first.
+    pub async fn transfer_attribute(
+        &mut self,
+        store_id: ActorId,
+        attribute_id: AttributeId,
+        to: ActorId,
+    ) {
+        let equipped = EquippedAttribute {
+            store_id,
+            attribute_id,
+        };
+        if self
+            .equipment
+            .values()
+            .any(|attribute| *attribute == equipped)
         {
-            if prev_account != account && prev_amount != amount {
-                msg::reply(TmgEvent::ApprovalError, 0)
-                    .expect("Error in sending a reply `TmgEvent::ApprovalError`");
+            msg::reply(TmgEvent::AttributeEquipped(attribute_id), 0)
+                .expect("Error in sending a reply `TmgEvent::AttributeEquipped`");
+            return;
+        }
+
+        let reply = msg::send_for_reply_as::<_, StoreEvent>(
+            store_id,
+            StoreAction::TransferAttribute { attribute_id, to },
+            0,
+            0,
+        )
+        .expect("Error in sending a message `StoreAction::TransferAttribute`")
+        .await;
+
+        let event = match reply {
+            Ok(StoreEvent::AttributeTransferred { .. }) => {
+                TmgEvent::AttributeTransferred { attribute_id, to }
+            }
+            _ => TmgEvent::AttributeNotOwned(attribute_id),
+        };
+        msg::reply(event, 0).expect("Error in sending a reply `TmgEvent::AttributeTransferred`");
+    }
+
+    pub async fn approve_tokens(&mut self, account: ActorId, amount: u128) {
+        let Some(transaction_id) = self.start_approval(account, amount) else {
+            msg::reply(TmgEvent::ApprovalError, 0)
+                .expect("Error in sending a reply `TmgEvent::ApprovalError`");
+            return;
+        };
+
+        if !self.send_approval(transaction_id, account, amount).await {
+            msg::reply(TmgEvent::ApprovalError, 0)
+                .expect("Error in sending a reply `TmgEvent::ApprovalError`");
+            return;
+        }
+
+        let response = TmgEvent::TokensApproved { account, amount };
+        msg::reply(response, 0).expect("Error in sending a reply `TmgEvent::ApprovalError`");
+    }
+
+    /// Approves the store for exactly the attribute price and buys it.
+    ///
+    /// The price, the approval and the store purchase are all kept across a
+    /// gas-out, so sending the same action again resumes the purchase with
+    /// the same transaction ids. A resumed purchase the store has already
+    /// completed is only confirmed, it isn't approved and paid again.
+    pub async fn approve_and_buy(&mut self, store_id: ActorId, attribute_id: AttributeId) {
+        let price = if let Some((prev_store_id, prev_attribute_id, price)) = self.pending_purchase {
+            if prev_store_id != store_id || prev_attribute_id != attribute_id {
+                msg::reply(TmgEvent::CompletePrevPurchase(prev_attribute_id), 0)
+                    .expect("Error in sending a reply `TmgEvent::CompletePrevPurchase`");
+                return;
+            }
+            if get_attributes(store_id).await.contains(&attribute_id) {
+                self.pending_purchase = None;
+                msg::reply(TmgEvent::AttributeBought(attribute_id), 0)
+                    .expect("Error in sending a reply `TmgEvent::AttributeBought`");
                 return;
             }
-            (prev_transaction_id, prev_account, prev_amount)
+            price
         } else {
-            let current_transaction_id = self.transaction_id;
-            self.transaction_id = self.transaction_id.wrapping_add(1);
-            self.approve_transaction = Some((current_transaction_id, account, amount));
-            (current_transaction_id, account, amount)
+            let price = get_attribute_price(store_id, attribute_id).await;
+            self.pending_purchase = Some((store_id, attribute_id, price));
+            price
+        };
+
+        let Some(transaction_id) = self.start_approval(store_id, price) else {
+            msg::reply(TmgEvent::ApprovalError, 0)
+                .expect("Error in sending a reply `TmgEvent::ApprovalError`");
+            return;
+        };
+        if !self.send_approval(transaction_id, store_id, price).await {
+            self.pending_purchase = None;
+            msg::reply(TmgEvent::ApprovalError, 0)
+                .expect("Error in sending a reply `TmgEvent::ApprovalError`");
+            return;
+        }
+
+        let store_response = msg::send_for_reply_as::<_, StoreEvent>(
+            store_id,
+            StoreAction::BuyAttribute { attribute_id },
+            0,
+            0,
+        )
+        .expect("Error in sending a message `StoreAction::BuyAttribute`")
+        .await;
+
+        let response = match store_response {
+            Ok(StoreEvent::AttributeSold { success: true }) => {
+                self.pending_purchase = None;
+                TmgEvent::AttributeBought(attribute_id)
+            }
+            // the purchase stays pending until the store completes its previous transaction
+            Ok(StoreEvent::CompletePrevTx { attribute_id }) => {
+                TmgEvent::CompletePrevPurchase(attribute_id)
+            }
+            // a store that rejected the purchase, e.g. because it's paused,
+            // has charged nothing, so the purchase is dropped
+            _ => {
+                self.pending_purchase = None;
+                TmgEvent::ErrorDuringPurchase
+            }
+        };
+        msg::reply(response, 0).expect("Error in sending a reply `TmgEvent::AttributeBought`");
+    }
+
+    // Returns the transaction id of the approval, reusing the pending one when
+    // the same approval is retried. A different pending approval must be
+    // completed first.
+    fn start_approval(&mut self, account: ActorId, amount: u128) -> Option<TransactionId> {
+        let transaction_id = match self.approve_transaction {
+            Some((prev_transaction_id, prev_account, prev_amount)) => {
+                if prev_account != account || prev_amount != amount {
+                    return None;
+                }
+                prev_transaction_id
+            }
+            None => {
+                let current_transaction_id = self.transaction_id;
+                self.transaction_id = self.transaction_id.wrapping_add(1);
+                self.approve_transaction = Some((current_transaction_id, account, amount));
+                current_transaction_id
+            }
         };
 
+        exec::system_reserve_gas(GAS_FOR_SIGNAL).expect("Error in reserving gas for a signal");
+        self.approval_message = Some(msg::id());
+        Some(transaction_id)
+    }
+
+    /// Drops the approval of a message that failed while waiting for it. An
+    /// approval only sets the allowance, so it's safe to send it again with a
+    /// new transaction id.
+    pub fn handle_signal(&mut self, failed: MessageId) {
+        if self.approval_message == Some(failed) {
+            self.approve_transaction = None;
+            self.approval_message = None;
+        }
+    }
+
+    async fn send_approval(
+        &mut self,
+        transaction_id: TransactionId,
+        account: ActorId,
+        amount: u128,
+    ) -> bool {
         let result_transaction = msg::send_for_reply_as::<_, FTokenEvent>(
             self.ft_contract_id,
             FTokenAction::Message {
@@ -112,17 +382,48 @@ impl Tamagotchi {
         .await
         .expect("Error in decoding 'FTokenEvent'");
 
-        if result_transaction != FTokenEvent::Ok {
-            msg::reply(TmgEvent::ApprovalError, 0)
-                .expect("Error in sending a reply `TmgEvent::ApprovalError`");
-            return;
-        }
-
-        let response = TmgEvent::TokensApproved { account, amount };
-        msg::reply(response, 0).expect("Error in sending a reply `TmgEvent::ApprovalError`");
+        self.approve_transaction = None;
+        self.approval_message = None;
+        result_transaction == FTokenEvent::Ok
     }
 }
 
+async fn get_attributes(store_id: ActorId) -> BTreeSet<AttributeId> {
+    let reply = msg::send_for_reply_as::<_, StoreEvent>(
+        store_id,
+        StoreAction::GetAttributes {
+            tamagotchi_id: exec::program_id(),
+        },
+        0,
+        0,
+    )
+    .expect("Error in sending a message `StoreAction::GetAttributes`")
+    .await
+    .expect("Error in decoding 'StoreEvent'");
+
+    let StoreEvent::Attributes { attributes } = reply else {
+        panic!("Wrong received message");
+    };
+    attributes
+}
+
+async fn get_attribute_price(store_id: ActorId, attribute_id: AttributeId) -> u128 {
+    let reply = msg::send_for_reply_as::<_, StoreEvent>(
+        store_id,
+        StoreAction::GetAttributePrice { attribute_id },
+        0,
+        0,
+    )
+    .expect("Error in sending a message `StoreAction::GetAttributePrice`")
+    .await
+    .expect("Error in decoding 'StoreEvent'");
+
+    let StoreEvent::AttributePrice { price, .. } = reply else {
+        panic!("Wrong received message");
+    };
+    price
+}
+
 pub struct ProgramMetadata;
 
 // TODO: 4️⃣ Fill `Init`, `Handle`, and `State` types
@@ -133,4 +434,4 @@ impl Metadata for ProgramMetadata {
     type Reply = ();
     type Others = ();
     type Signal = ();
-}
\ No newline at end of file
+}
@@ -24,7 +24,7 @@ extern fn init() {
     let tmg = Tamagotchi {
         name: initname,
         date_of_birth: birthdate,
-        owner: Default::default(),
+        owner: msg::source(),
         fed: 1,
         fed_block: exec::block_height() as u64,
         entertained: 1,
@@ -35,14 +35,17 @@ extern fn init() {
         ft_contract_id: ActorId::from(1),
         transaction_id: 1,
         approve_transaction: None,
+        approval_message: None,
+        pending_purchase: None,
+        equipment: Default::default(),
     };
     unsafe {
         TAMAGOTCHI = Some(tmg);
     };
 }
 
-#[no_mangle]
-async fn handle() {
+#[gstd::async_main(handle_signal = signal)]
+async fn main() {
     let action: TmgAction = msg::load().expect("Unable to decode `TmgAction`");
     let tmg = unsafe { TAMAGOTCHI.get_or_insert(Default::default()) };
     match action {
@@ -118,24 +121,30 @@ async fn handle() {
             msg::reply(TmgEvent::FTokenContractSet, 0).expect("Error in sending reply");
         }
         TmgAction::ApproveTokens { account, amount } => {
-            tmg.approve_tokens(account, amount);
+            tmg.approve_tokens(account, amount).await;
         }
         TmgAction::BuyAttribute {
             store_id,
             attribute_id,
         } => {
+            assert!(
+                tmg.is_owner_or_approved(&msg::source()),
+                "Only the owner or an approved account can buy attributes"
+            );
+            // the attached value pays for the attribute in native currency,
+            // without value the store charges fungible tokens
+            let value = msg::value();
             let result = msg::send_for_reply_as::<_, StoreEvent>(
                 store_id,
                 StoreAction::BuyAttribute { attribute_id },
-                0,
+                value,
                 0,
             )
             .expect("Error al enviar mensaje `StoreAction::BuyAttribute`")
-            .await
-            .expect("Error al decodificar 'StoreEvent'");
+            .await;
 
             match result {
-                StoreEvent::AttributeSold { success } => {
+                Ok(StoreEvent::AttributeSold { success }) => {
                     if success {
                         msg::reply(TmgEvent::AttributeBought(attribute_id), 0)
                             .expect("Error al enviar respuesta `TmgEvent::AttributeBought`");
@@ -144,15 +153,143 @@ async fn handle() {
                             .expect("Error al enviar respuesta `TmgEvent::ErrorDuringPurchase`");
                     }
                 }
+                Ok(StoreEvent::AttributeSoldForValue { change }) => {
+                    msg::reply(TmgEvent::AttributeBought(attribute_id), change)
+                        .expect("Error in sending a reply `TmgEvent::AttributeBought`");
+                }
                 _ => {
-                    msg::reply(TmgEvent::ErrorDuringPurchase, 0)
+                    // a rejected native payment is returned with the store's error reply
+                    msg::reply(TmgEvent::ErrorDuringPurchase, value)
                         .expect("Error al enviar respuesta `TmgEvent::ErrorDuringPurchase`");
                 }
             }
         }
+        TmgAction::ApproveAndBuy {
+            store_id,
+            attribute_id,
+        } => {
+            assert!(
+                tmg.is_owner_or_approved(&msg::source()),
+                "Only the owner or an approved account can buy attributes"
+            );
+            tmg.approve_and_buy(store_id, attribute_id).await;
+        }
+        TmgAction::Equip {
+            store_id,
+            attribute_id,
+            slot,
+        } => {
+            assert!(
+                tmg.is_owner_or_approved(&msg::source()),
+                "Only the owner or an approved account can manage the attributes"
+            );
+            tmg.equip(store_id, attribute_id, slot).await;
+        }
+        TmgAction::Unequip { slot } => {
+            assert!(
+                tmg.is_owner_or_approved(&msg::source()),
+                "Only the owner or an approved account can manage the attributes"
+            );
+            tmg.unequip(slot);
+        }
+        TmgAction::TransferAttribute {
+            store_id,
+            attribute_id,
+            to,
+        } => {
+            assert!(
+                tmg.is_owner_or_approved(&msg::source()),
+                "Only the owner or an approved account can manage the attributes"
+            );
+            tmg.transfer_attribute(store_id, attribute_id, to).await;
+        }
+        TmgAction::GiftAttribute {
+            store_id,
+            attribute_id,
+            to,
+        } => {
+            assert!(
+                tmg.is_owner_or_approved(&msg::source()),
+                "Only the owner or an approved account can buy attributes"
+            );
+            // the gift is paid like `BuyAttribute`, with the attached value
+            // or with the Tamagotchi's fungible tokens
+            let value = msg::value();
+            let result = msg::send_for_reply_as::<_, StoreEvent>(
+                store_id,
+                StoreAction::GiftAttribute { attribute_id, to },
+                value,
+                0,
+            )
+            .expect("Error in sending a message `StoreAction::GiftAttribute`")
+            .await;
+
+            match result {
+                Ok(StoreEvent::AttributeGifted {
+                    success: true,
+                    change,
+                    ..
+                }) => {
+                    msg::reply(TmgEvent::AttributeGifted { attribute_id, to }, change)
+                        .expect("Error in sending a reply `TmgEvent::AttributeGifted`");
+                }
+                Ok(_) => {
+                    msg::reply(TmgEvent::ErrorDuringPurchase, 0)
+                        .expect("Error in sending a reply `TmgEvent::ErrorDuringPurchase`");
+                }
+                Err(_) => {
+                    msg::reply(TmgEvent::ErrorDuringPurchase, value)
+                        .expect("Error in sending a reply `TmgEvent::ErrorDuringPurchase`");
+                }
+            }
+        }
+        TmgAction::BuyBundle {
+            store_id,
+            bundle_id,
+        } => {
+            assert!(
+                tmg.is_owner_or_approved(&msg::source()),
+                "Only the owner or an approved account can buy attributes"
+            );
+            let result = msg::send_for_reply_as::<_, StoreEvent>(
+                store_id,
+                StoreAction::BuyBundle { bundle_id },
+                0,
+                0,
+            )
+            .expect("Error in sending a message `StoreAction::BuyBundle`")
+            .await
+            .expect("Error in decoding 'StoreEvent'");
+
+            let event = match result {
+                StoreEvent::BundleSold { success: true } => TmgEvent::BundleBought(bundle_id),
+                _ => TmgEvent::ErrorDuringPurchase,
+            };
+            msg::reply(event, 0).expect("Error in sending a reply `TmgEvent::BundleBought`");
+        }
+        TmgAction::AttributeReceived { attribute_id, from } => {
+            // the store that moved the attribute sends the notification,
+            // it's passed on to the owner
+            msg::send(
+                tmg.owner,
+                TmgEvent::AttributeReceived {
+                    store_id: msg::source(),
+                    attribute_id,
+                    from,
+                },
+                0,
+            )
+            .expect("Error in sending a message `TmgEvent::AttributeReceived`");
+        }
     }
 }
 
+fn signal() {
+    let failed = msg::signal_from().expect("Error in getting the failed message");
+    let tmg = unsafe { TAMAGOTCHI.get_or_insert(Default::default()) };
+    tmg.handle_signal(failed);
+}
+
 #[no_mangle]
 extern fn state() {
     let tmg = unsafe { TAMAGOTCHI.take().expect("Unexpected error in taking state") };
@@ -1,16 +1,30 @@
 #![no_std]
 
+use access_control::{AccessControl, Role};
 use gmeta::{In, InOut, Metadata as GMetadata, Out};
 use gstd::{
     collections::{BTreeMap, BTreeSet},
     prelude::*,
-    ActorId,
+    ActorId, MessageId,
 };
 
 pub type AttributeId = u128;
 pub type Price = u128;
 pub type TamagotchiId = ActorId;
 pub type TransactionId = u64;
+pub type BasisPoints = u16;
+pub type BundleId = u128;
+
+/// Sum of the revenue split shares, 100% expressed in basis points.
+pub const MAX_BASIS_POINTS: BasisPoints = 10_000;
+/// Gas reserved when an auction is created to run its delayed settlement.
+pub const GAS_FOR_SETTLEMENT: u64 = 10_000_000_000;
+/// Blocks after which a pending transaction is considered stuck and can be
+/// resolved by anyone.
+pub const TX_EXPIRATION_BLOCKS: u32 = 600;
+/// Gas kept aside by a message waiting for a reply to handle the signal if
+/// it fails.
+pub const GAS_FOR_SIGNAL: u64 = 1_000_000_000;
 
 pub struct ProgramMetadata;
 
@@ -27,12 +41,23 @@ impl GMetadata for ProgramMetadata {
 #[codec(crate = gstd::codec)]
 #[scale_info(crate = gstd::scale_info)]
 pub struct AttributeStore {
-    pub admin: ActorId,
+    pub access_control: AccessControl,
+    pub paused: bool,
     pub ft_contract_id: ActorId,
     pub attributes: BTreeMap<AttributeId, (AttrMetadata, Price)>,
     pub owners: BTreeMap<TamagotchiId, BTreeSet<AttributeId>>,
     pub transaction_id: TransactionId,
-    pub transactions: BTreeMap<TamagotchiId, (TransactionId, AttributeId)>,
+    pub transactions: BTreeMap<TamagotchiId, PendingTx>,
+    pub revenue_split: Vec<(ActorId, BasisPoints)>,
+    pub balances: BTreeMap<ActorId, u128>,
+    pub withdrawals: BTreeMap<ActorId, (TransactionId, ActorId, u128)>,
+    pub bundles: BTreeMap<BundleId, Bundle>,
+    pub native_prices: BTreeMap<AttributeId, Price>,
+    pub native_balances: BTreeMap<ActorId, u128>,
+    pub sales: BTreeMap<AttributeId, Sale>,
+    pub auctions: BTreeMap<AttributeId, Auction>,
+    /// The sales log, a receipt's index is its sequence number.
+    pub receipts: Vec<Receipt>,
 }
 
 #[derive(Encode, Decode, Clone, TypeInfo, Debug)]
@@ -44,6 +69,98 @@ pub struct AttrMetadata {
     pub media: String,
 }
 
+#[derive(Encode, Decode, Clone, TypeInfo, Debug)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub struct Bundle {
+    pub attributes: BTreeSet<AttributeId>,
+    pub price: Price,
+    /// Maximum number of units that can be sold, `None` for an unlimited bundle.
+    pub supply: Option<u32>,
+    /// Units sold or reserved by pending purchases.
+    pub sold: u32,
+}
+
+/// A discount applied to an attribute price from `start_block` until `end_block`.
+#[derive(Encode, Decode, Clone, TypeInfo, Debug)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub struct Sale {
+    pub start_block: u32,
+    pub end_block: u32,
+    pub discount_percent: u8,
+}
+
+/// An English auction for a one-off attribute. The highest bid is held by the
+/// store until the auction is settled.
+#[derive(Encode, Decode, Clone, TypeInfo, Debug)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub struct Auction {
+    pub min_bid: u128,
+    pub end_block: u32,
+    pub highest_bid: Option<(TamagotchiId, u128)>,
+    pub settled: bool,
+}
+
+#[derive(Encode, Decode, Clone, Copy, TypeInfo, Debug, PartialEq, Eq)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub enum Currency {
+    FungibleToken,
+    Native,
+}
+
+/// What a pending transaction is paying for.
+#[derive(Encode, Decode, Clone, Copy, TypeInfo, Debug, PartialEq, Eq)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub enum Purchase {
+    Attribute(AttributeId),
+    Bundle(BundleId),
+    Bid {
+        attribute_id: AttributeId,
+        amount: u128,
+    },
+    Gift {
+        attribute_id: AttributeId,
+        to: TamagotchiId,
+    },
+}
+
+/// A fungible token transfer that has been started but not completed yet.
+#[derive(Encode, Decode, Clone, Copy, TypeInfo, Debug, PartialEq, Eq)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub struct PendingTx {
+    pub transaction_id: TransactionId,
+    pub purchase: Purchase,
+    /// The amount of tokens being transferred, fixed when the transaction starts.
+    pub amount: u128,
+    pub created_at: u32,
+    pub retries: u32,
+    /// The message processing the transaction.
+    pub message_id: MessageId,
+    /// The message ran out of gas or failed before completing the
+    /// transaction, so anyone can recover it without waiting for it to expire.
+    pub interrupted: bool,
+}
+
+/// An entry of the append-only sales log.
+#[derive(Encode, Decode, Clone, Copy, TypeInfo, Debug, PartialEq, Eq)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub struct Receipt {
+    /// The fungible token transfer of the sale, or an id given by the store
+    /// to native value payments and auction wins.
+    pub transaction_id: TransactionId,
+    pub buyer: TamagotchiId,
+    pub purchase: Purchase,
+    pub price: Price,
+    pub currency: Currency,
+    pub block: u32,
+}
+
 #[derive(Encode, Decode, TypeInfo, Debug)]
 #[codec(crate = gstd::codec)]
 #[scale_info(crate = gstd::scale_info)]
@@ -65,11 +182,87 @@ pub enum StoreAction {
     RemoveTx {
         tamagotchi_id: TamagotchiId,
     },
+    SetRevenueSplit {
+        recipients: Vec<(ActorId, BasisPoints)>,
+    },
+    Withdraw {
+        to: ActorId,
+        amount: u128,
+    },
+    GrantRole {
+        role: Role,
+        account: ActorId,
+    },
+    RevokeRole {
+        role: Role,
+        account: ActorId,
+    },
+    TransferOwnership {
+        new_owner: ActorId,
+    },
+    AcceptOwnership,
+    Pause,
+    Unpause,
+    CreateBundle {
+        bundle_id: BundleId,
+        attributes: BTreeSet<AttributeId>,
+        price: Price,
+        supply: Option<u32>,
+    },
+    BuyBundle {
+        bundle_id: BundleId,
+    },
+    SetNativePrice {
+        attribute_id: AttributeId,
+        price: Option<Price>,
+    },
+    WithdrawNative {
+        to: ActorId,
+        amount: u128,
+    },
+    GetAttributePrice {
+        attribute_id: AttributeId,
+    },
+    CreateSale {
+        attribute_id: AttributeId,
+        start_block: u32,
+        end_block: u32,
+        discount_percent: u8,
+    },
+    CancelSale {
+        attribute_id: AttributeId,
+    },
+    CreateAuction {
+        attribute_id: AttributeId,
+        min_bid: u128,
+        duration: u32,
+    },
+    Bid {
+        attribute_id: AttributeId,
+        amount: u128,
+    },
+    SettleAuction {
+        attribute_id: AttributeId,
+    },
+    RecoverTx {
+        tamagotchi_id: TamagotchiId,
+    },
+    GetStuckTxs,
+    TransferAttribute {
+        attribute_id: AttributeId,
+        to: TamagotchiId,
+    },
+    /// Buys the attribute for `to`, the sender pays.
+    GiftAttribute {
+        attribute_id: AttributeId,
+        to: TamagotchiId,
+    },
 }
 
 #[derive(Encode, Decode, TypeInfo)]
 #[codec(crate = gstd::codec)]
 #[scale_info(crate = gstd::scale_info)]
+#[rustfmt::skip]
 pub enum StoreEvent {
     AttributeCreated { attribute_id: AttributeId },
     AttributeSold { success: bool },
@@ -77,4 +270,97 @@ pub enum StoreEvent {
     CompletePrevTx { attribute_id: AttributeId },
     FtContractIdSet { ft_contract_id: ActorId },
     TxRemoved { tamagotchi_id: ActorId },
+    RevenueSplitSet {
+        recipients: Vec<(ActorId, BasisPoints)>,
+    },
+    Withdrawn {
+        success: bool,
+    },
+    CompletePrevWithdrawal {
+        to: ActorId,
+        amount: u128,
+    },
+    RoleGranted {
+        role: Role,
+        account: ActorId,
+    },
+    RoleRevoked {
+        role: Role,
+        account: ActorId,
+    },
+    OwnershipTransferStarted {
+        new_owner: ActorId,
+    },
+    OwnershipTransferred {
+        new_owner: ActorId,
+    },
+    Paused,
+    Unpaused,
+    BundleCreated {
+        bundle_id: BundleId,
+    },
+    BundleSold {
+        success: bool,
+    },
+    CompletePrevBundleTx {
+        bundle_id: BundleId,
+    },
+    AttributeSoldForValue {
+        change: u128,
+    },
+    NativePriceSet {
+        attribute_id: AttributeId,
+        price: Option<Price>,
+    },
+    NativeWithdrawn {
+        to: ActorId,
+        amount: u128,
+    },
+    AttributePrice {
+        attribute_id: AttributeId,
+        price: Price,
+    },
+    SaleCreated {
+        attribute_id: AttributeId,
+    },
+    SaleCancelled {
+        attribute_id: AttributeId,
+    },
+    AuctionCreated {
+        attribute_id: AttributeId,
+        end_block: u32,
+    },
+    BidPlaced {
+        success: bool,
+    },
+    CompletePrevBid {
+        attribute_id: AttributeId,
+        amount: u128,
+    },
+    AuctionSettled {
+        attribute_id: AttributeId,
+        winner: Option<TamagotchiId>,
+    },
+    TxRecovered {
+        tamagotchi_id: TamagotchiId,
+        completed: bool,
+    },
+    StuckTxs {
+        transactions: Vec<(TamagotchiId, PendingTx)>,
+    },
+    AttributeTransferred {
+        attribute_id: AttributeId,
+        to: TamagotchiId,
+    },
+    AttributeGifted {
+        attribute_id: AttributeId,
+        to: TamagotchiId,
+        success: bool,
+        /// The native value returned with this reply.
+        change: u128,
+    },
+    CompletePrevGift {
+        attribute_id: AttributeId,
+        to: TamagotchiId,
+    },
 }
@@ -1,26 +1,40 @@
 #![no_std]
 
+use access_control::{AccessControl, Role};
 use gstd::{
     collections::{BTreeMap, BTreeSet},
     exec, msg,
     prelude::*,
-    ActorId,
+    ActorId, ReservationId,
 };
 use sharded_fungible_token_io::{FTokenAction, FTokenEvent, LogicAction};
 use store_io::{
-    AttrMetadata, AttributeId, Price, StoreAction, StoreEvent, TamagotchiId, TransactionId,
+    AttrMetadata, AttributeId, Auction, BasisPoints, Bundle, BundleId, Currency, PendingTx, Price,
+    Purchase, Receipt, Sale, StoreAction, StoreEvent, TamagotchiId, TransactionId,
+    GAS_FOR_SETTLEMENT, GAS_FOR_SIGNAL, MAX_BASIS_POINTS, TX_EXPIRATION_BLOCKS,
 };
+use tamagotchi_shop_io::TmgAction;
 
 static mut STORE: Option<AttributeStore> = None;
 
 #[derive(Default)]
 struct AttributeStore {
-    admin: ActorId,
+    access_control: AccessControl,
+    paused: bool,
     ft_contract_id: ActorId,
     attributes: BTreeMap<AttributeId, (AttrMetadata, Price)>,
     owners: BTreeMap<TamagotchiId, BTreeSet<AttributeId>>,
     transaction_id: TransactionId,
-    transactions: BTreeMap<TamagotchiId, (TransactionId, AttributeId)>,
+    transactions: BTreeMap<TamagotchiId, PendingTx>,
+    revenue_split: Vec<(ActorId, BasisPoints)>,
+    balances: BTreeMap<ActorId, u128>,
+    withdrawals: BTreeMap<ActorId, (TransactionId, ActorId, u128)>,
+    bundles: BTreeMap<BundleId, Bundle>,
+    native_prices: BTreeMap<AttributeId, Price>,
+    native_balances: BTreeMap<ActorId, u128>,
+    sales: BTreeMap<AttributeId, Sale>,
+    auctions: BTreeMap<AttributeId, Auction>,
+    receipts: Vec<Receipt>,
 }
 
 impl AttributeStore {
@@ -30,7 +44,7 @@ impl AttributeStore {
         metadata: &AttrMetadata,
         price: Price,
     ) {
-        assert_eq!(msg::source(), self.admin, "Only admin can add attributes");
+        self.access_control.check_role(Role::CatalogManager);
 
         if self
             .attributes
@@ -44,66 +58,371 @@ impl AttributeStore {
             .expect("Error in sending a reply `StoreEvent::AttributeCreated");
     }
     async fn buy_attribute(&mut self, attribute_id: AttributeId) {
-        let (transaction_id, attribute_id) = if let Some((transaction_id, prev_attribute_id)) =
-            self.transactions.get(&msg::source())
-        {
-            // if `prev_attribute_id` is not equal to `attribute_id` then it means that transaction didn`t completed
-            // we ask the tamagotchi contract to complete the previous transaction
-            if attribute_id != *prev_attribute_id {
-                msg::reply(
-                    StoreEvent::CompletePrevTx {
-                        attribute_id: *prev_attribute_id,
-                    },
-                    0,
-                )
-                .expect("Error in sending a reply `StoreEvent::CompletePrevTx`");
-                return;
-            }
-            (*transaction_id, *prev_attribute_id)
-        } else {
-            let current_transaction_id = self.transaction_id;
-            self.transaction_id = self.transaction_id.wrapping_add(1);
-            self.transactions
-                .insert(msg::source(), (current_transaction_id, attribute_id));
-            (current_transaction_id, attribute_id)
+        assert!(!self.paused, "The store is paused");
+        assert!(
+            !self.auctions.contains_key(&attribute_id),
+            "The attribute is only sold by auction"
+        );
+
+        if msg::value() > 0 {
+            let change = self.sell_attribute_for_value(attribute_id, msg::source());
+            msg::reply(StoreEvent::AttributeSoldForValue { change }, change)
+                .expect("Error in sending a reply `StoreEvent::AttributeSoldForValue`");
+            return;
+        }
+
+        let &(_, price) = self
+            .attributes
+            .get(&attribute_id)
+            .expect("Can`t get attribute_id");
+        let price = self.discounted_price(attribute_id, price);
+        let Some(pending) = self
+            .start_transaction(Purchase::Attribute(attribute_id), price)
+            .await
+        else {
+            return;
         };
 
-        let result = self.sell_attribute(transaction_id, attribute_id).await;
-        self.transactions.remove(&msg::source());
+        let result = self.complete_transaction(msg::source(), pending).await;
 
         msg::reply(StoreEvent::AttributeSold { success: result }, 0)
             .expect("Error in sending a reply `StoreEvent::AttributeSold`");
     }
 
-    async fn sell_attribute(
+    // Native value arrives with the message, so unlike the fungible token
+    // purchase there is nothing to await and no transaction to track.
+    // Returns the change to send back to the buyer.
+    fn sell_attribute_for_value(
         &mut self,
-        transaction_id: TransactionId,
         attribute_id: AttributeId,
-    ) -> bool {
-        let (_, price) = self
+        recipient: TamagotchiId,
+    ) -> u128 {
+        let price = *self
+            .native_prices
+            .get(&attribute_id)
+            .expect("The attribute can`t be bought with native value");
+        let price = self.discounted_price(attribute_id, price);
+        let value = msg::value();
+        assert!(value >= price, "Not enough value to buy the attribute");
+
+        self.distribute_revenue(price, Currency::Native);
+        self.owners
+            .entry(recipient)
+            .or_default()
+            .insert(attribute_id);
+
+        let buyer = msg::source();
+        let purchase = if recipient == buyer {
+            Purchase::Attribute(attribute_id)
+        } else {
+            Purchase::Gift {
+                attribute_id,
+                to: recipient,
+            }
+        };
+        self.record_sale(None, buyer, purchase, price, Currency::Native);
+
+        value - price
+    }
+
+    async fn gift_attribute(&mut self, attribute_id: AttributeId, to: TamagotchiId) {
+        assert!(!self.paused, "The store is paused");
+        assert!(
+            !self.auctions.contains_key(&attribute_id),
+            "The attribute is only sold by auction"
+        );
+
+        let buyer = msg::source();
+        if msg::value() > 0 {
+            let change = self.sell_attribute_for_value(attribute_id, to);
+            notify_recipient(attribute_id, buyer, to);
+            msg::reply(
+                StoreEvent::AttributeGifted {
+                    attribute_id,
+                    to,
+                    success: true,
+                    change,
+                },
+                change,
+            )
+            .expect("Error in sending a reply `StoreEvent::AttributeGifted`");
+            return;
+        }
+
+        let &(_, price) = self
             .attributes
             .get(&attribute_id)
             .expect("Can`t get attribute_id");
+        let price = self.discounted_price(attribute_id, price);
+        let Some(pending) = self
+            .start_transaction(Purchase::Gift { attribute_id, to }, price)
+            .await
+        else {
+            return;
+        };
 
-        if transfer_tokens(
-            transaction_id,
+        let result = self.complete_transaction(buyer, pending).await;
+
+        msg::reply(
+            StoreEvent::AttributeGifted {
+                attribute_id,
+                to,
+                success: result,
+                change: 0,
+            },
+            0,
+        )
+        .expect("Error in sending a reply `StoreEvent::AttributeGifted`");
+    }
+
+    // Moves an attribute of the sender to another Tamagotchi. Equipment is
+    // kept by the Tamagotchi contract, which refuses to transfer an equipped
+    // attribute.
+    fn transfer_attribute(&mut self, attribute_id: AttributeId, to: TamagotchiId) {
+        let from = msg::source();
+        let removed = self
+            .owners
+            .get_mut(&from)
+            .is_some_and(|attributes| attributes.remove(&attribute_id));
+        assert!(removed, "The attribute isn`t owned by the sender");
+        self.owners.entry(to).or_default().insert(attribute_id);
+
+        notify_recipient(attribute_id, from, to);
+        msg::reply(StoreEvent::AttributeTransferred { attribute_id, to }, 0)
+            .expect("Error in sending a reply `StoreEvent::AttributeTransferred`");
+    }
+
+    // Returns the pending transaction for `purchase`, reusing the pending
+    // transaction when the same purchase is retried. A stuck transaction of
+    // another purchase is resolved first so that it doesn`t block the buyer.
+    async fn start_transaction(&mut self, purchase: Purchase, amount: u128) -> Option<PendingTx> {
+        let buyer = msg::source();
+        if let Some(pending) = self.transactions.get_mut(&buyer) {
+            if pending.purchase == purchase {
+                exec::system_reserve_gas(GAS_FOR_SIGNAL)
+                    .expect("Error in reserving gas for a signal");
+                pending.retries += 1;
+                pending.message_id = msg::id();
+                pending.interrupted = false;
+                return Some(*pending);
+            }
+
+            // the previous transaction didn`t complete, until it gets stuck
+            // we ask the tamagotchi contract to complete it
+            if !is_stuck(pending) {
+                let event = match pending.purchase {
+                    Purchase::Attribute(attribute_id) => {
+                        StoreEvent::CompletePrevTx { attribute_id }
+                    }
+                    Purchase::Bundle(bundle_id) => StoreEvent::CompletePrevBundleTx { bundle_id },
+                    Purchase::Bid {
+                        attribute_id,
+                        amount,
+                    } => StoreEvent::CompletePrevBid {
+                        attribute_id,
+                        amount,
+                    },
+                    Purchase::Gift { attribute_id, to } => {
+                        StoreEvent::CompletePrevGift { attribute_id, to }
+                    }
+                };
+                msg::reply(event, 0)
+                    .expect("Error in sending a reply `StoreEvent::CompletePrevTx`");
+                return None;
+            }
+
+            let pending = *pending;
+            self.complete_transaction(buyer, pending).await;
+        }
+
+        exec::system_reserve_gas(GAS_FOR_SIGNAL).expect("Error in reserving gas for a signal");
+        let pending = PendingTx {
+            transaction_id: self.next_transaction_id(),
+            purchase,
+            amount,
+            created_at: exec::block_height(),
+            retries: 0,
+            message_id: msg::id(),
+            interrupted: false,
+        };
+        self.transactions.insert(buyer, pending);
+        Some(pending)
+    }
+
+    // Transfers the tokens of a pending transaction and applies the purchase.
+    // The transfer reuses the transaction id, so if it has already been
+    // executed the fungible token contract only returns its result.
+    async fn complete_transaction(&mut self, buyer: TamagotchiId, pending: PendingTx) -> bool {
+        let result = transfer_tokens(
+            pending.transaction_id,
             &self.ft_contract_id,
-            &msg::source(),
+            &buyer,
             &exec::program_id(),
-            *price,
+            pending.amount,
         )
         .await
-        .is_ok()
-        {
-            self.owners
-                .entry(msg::source())
-                .and_modify(|attributes| {
-                    attributes.insert(attribute_id);
-                })
-                .or_insert_with(|| [attribute_id].into());
-            return true;
+        .is_ok();
+
+        // another message could have completed the same transaction while
+        // this one was waiting for the transfer, it's only applied once
+        if self.transactions.get(&buyer).map_or(true, |current| {
+            current.transaction_id != pending.transaction_id
+        }) {
+            return result;
+        }
+        self.transactions.remove(&buyer);
+
+        // a bid is only a sale once its auction is settled
+        if result && !matches!(pending.purchase, Purchase::Bid { .. }) {
+            self.record_sale(
+                Some(pending.transaction_id),
+                buyer,
+                pending.purchase,
+                pending.amount,
+                Currency::FungibleToken,
+            );
+        }
+
+        match pending.purchase {
+            Purchase::Attribute(attribute_id) => {
+                if result {
+                    self.distribute_revenue(pending.amount, Currency::FungibleToken);
+                    self.owners.entry(buyer).or_default().insert(attribute_id);
+                }
+                result
+            }
+            Purchase::Bundle(bundle_id) => {
+                if result {
+                    self.distribute_revenue(pending.amount, Currency::FungibleToken);
+                    let attributes = self.bundles[&bundle_id].attributes.clone();
+                    self.owners.entry(buyer).or_default().extend(attributes);
+                } else if let Some(bundle) = self.bundles.get_mut(&bundle_id) {
+                    // releases the unit reserved when the transaction started
+                    bundle.sold -= 1;
+                }
+                result
+            }
+            Purchase::Gift { attribute_id, to } => {
+                if result {
+                    self.distribute_revenue(pending.amount, Currency::FungibleToken);
+                    self.owners.entry(to).or_default().insert(attribute_id);
+                    notify_recipient(attribute_id, buyer, to);
+                }
+                result
+            }
+            Purchase::Bid { attribute_id, .. } => {
+                result && self.place_bid(buyer, attribute_id, pending.amount).await
+            }
         }
-        false
+    }
+
+    async fn recover_tx(&mut self, tmg_id: TamagotchiId) {
+        let pending = *self
+            .transactions
+            .get(&tmg_id)
+            .expect("There is no pending transaction");
+        // a transaction that is still being processed could complete later,
+        // so it can't be recovered even by the owner
+        assert!(is_stuck(&pending), "The transaction hasn`t expired yet");
+
+        let completed = self.complete_transaction(tmg_id, pending).await;
+
+        msg::reply(
+            StoreEvent::TxRecovered {
+                tamagotchi_id: tmg_id,
+                completed,
+            },
+            0,
+        )
+        .expect("Error in sending a reply `StoreEvent::TxRecovered`");
+    }
+
+    fn get_stuck_txs(&self) {
+        let transactions = self
+            .transactions
+            .iter()
+            .filter(|(_, pending)| is_stuck(pending))
+            .map(|(tmg_id, pending)| (*tmg_id, *pending))
+            .collect();
+        msg::reply(StoreEvent::StuckTxs { transactions }, 0)
+            .expect("Error in sending a reply `StoreEvent::StuckTxs`");
+    }
+
+    fn create_bundle(
+        &mut self,
+        bundle_id: BundleId,
+        attributes: BTreeSet<AttributeId>,
+        price: Price,
+        supply: Option<u32>,
+    ) {
+        self.access_control.check_role(Role::CatalogManager);
+        assert!(!attributes.is_empty(), "Bundle must contain attributes");
+        assert!(
+            attributes
+                .iter()
+                .all(|attribute_id| self.attributes.contains_key(attribute_id)),
+            "Bundle contains an attribute that doesn`t exist"
+        );
+        assert!(
+            attributes
+                .iter()
+                .all(|attribute_id| !self.auctions.contains_key(attribute_id)),
+            "Bundle contains an attribute that is only sold by auction"
+        );
+
+        let bundle = Bundle {
+            attributes,
+            price,
+            supply,
+            sold: 0,
+        };
+        if self.bundles.insert(bundle_id, bundle).is_some() {
+            panic!("Bundle with that ID already exists");
+        }
+
+        msg::reply(StoreEvent::BundleCreated { bundle_id }, 0)
+            .expect("Error in sending a reply `StoreEvent::BundleCreated`");
+    }
+
+    async fn buy_bundle(&mut self, bundle_id: BundleId) {
+        assert!(!self.paused, "The store is paused");
+
+        let bundle = self.bundles.get(&bundle_id).expect("Can`t get bundle_id");
+        // an attribute of the bundle could have been auctioned after the
+        // bundle was created
+        assert!(
+            bundle
+                .attributes
+                .iter()
+                .all(|attribute_id| !self.auctions.contains_key(attribute_id)),
+            "The bundle contains an attribute that is only sold by auction"
+        );
+        let price = bundle.price;
+        let Some(pending) = self
+            .start_transaction(Purchase::Bundle(bundle_id), price)
+            .await
+        else {
+            return;
+        };
+
+        let bundle = self
+            .bundles
+            .get_mut(&bundle_id)
+            .expect("Can`t get bundle_id");
+        // a unit is reserved when the transaction starts so that concurrent
+        // purchases can't oversell the bundle
+        if pending.retries == 0 {
+            assert!(
+                bundle.supply.map_or(true, |supply| bundle.sold < supply),
+                "The bundle is sold out"
+            );
+            bundle.sold += 1;
+        }
+
+        let result = self.complete_transaction(msg::source(), pending).await;
+
+        msg::reply(StoreEvent::BundleSold { success: result }, 0)
+            .expect("Error in sending a reply `StoreEvent::BundleSold`");
     }
 
     fn get_attributes(&self, tmg_id: &TamagotchiId) {
@@ -112,12 +431,24 @@ impl AttributeStore {
             .expect("Error in sending a reply `StoreEvent::Attributes`");
     }
 
+    fn get_attribute_price(&self, attribute_id: AttributeId) {
+        let &(_, price) = self
+            .attributes
+            .get(&attribute_id)
+            .expect("Can`t get attribute_id");
+        let price = self.discounted_price(attribute_id, price);
+        msg::reply(
+            StoreEvent::AttributePrice {
+                attribute_id,
+                price,
+            },
+            0,
+        )
+        .expect("Error in sending a reply `StoreEvent::AttributePrice`");
+    }
+
     fn set_ft_contract_id(&mut self, ft_contract_id: &ActorId) {
-        assert_eq!(
-            msg::source(),
-            self.admin,
-            "Only admin can set fungible token contract"
-        );
+        self.access_control.check_role(Role::Owner);
         self.ft_contract_id = *ft_contract_id;
         msg::reply(
             StoreEvent::FtContractIdSet {
@@ -129,12 +460,17 @@ impl AttributeStore {
     }
 
     fn remove_tx(&mut self, tmg_id: &TamagotchiId) {
-        assert_eq!(
-            msg::source(),
-            self.admin,
-            "Only admin can set remove transactions"
-        );
-        self.transactions.remove(tmg_id);
+        self.access_control.check_role(Role::Owner);
+        if let Some(PendingTx {
+            purchase: Purchase::Bundle(bundle_id),
+            ..
+        }) = self.transactions.remove(tmg_id)
+        {
+            // releases the unit reserved when the transaction started
+            if let Some(bundle) = self.bundles.get_mut(&bundle_id) {
+                bundle.sold -= 1;
+            }
+        }
         msg::reply(
             StoreEvent::TxRemoved {
                 tamagotchi_id: *tmg_id,
@@ -143,9 +479,419 @@ impl AttributeStore {
         )
         .expect("Error in sending a reply `StoreEvent::TxRemoved`");
     }
+
+    fn set_revenue_split(&mut self, recipients: Vec<(ActorId, BasisPoints)>) {
+        self.access_control.check_role(Role::Treasurer);
+        let total_shares: u32 = recipients.iter().map(|(_, share)| u32::from(*share)).sum();
+        assert_eq!(
+            total_shares,
+            u32::from(MAX_BASIS_POINTS),
+            "Revenue split shares must add up to 10000 basis points"
+        );
+
+        self.revenue_split = recipients.clone();
+        msg::reply(StoreEvent::RevenueSplitSet { recipients }, 0)
+            .expect("Error in sending a reply `StoreEvent::RevenueSplitSet`");
+    }
+
+    // Credits the sale `amount` to the revenue split recipients. Until a split
+    // is configured the whole amount belongs to the owner.
+    fn distribute_revenue(&mut self, amount: u128, currency: Currency) {
+        let balances = match currency {
+            Currency::FungibleToken => &mut self.balances,
+            Currency::Native => &mut self.native_balances,
+        };
+        let Some(&(first_recipient, _)) = self.revenue_split.first() else {
+            *balances.entry(self.access_control.owner).or_default() += amount;
+            return;
+        };
+
+        let max_basis_points = u128::from(MAX_BASIS_POINTS);
+        let mut distributed = 0;
+        for (recipient, share) in &self.revenue_split {
+            let share = u128::from(*share);
+            let part = amount / max_basis_points * share
+                + amount % max_basis_points * share / max_basis_points;
+            *balances.entry(*recipient).or_default() += part;
+            distributed += part;
+        }
+
+        // the rounding remainder goes to the first recipient
+        *balances.entry(first_recipient).or_default() += amount - distributed;
+    }
+
+    async fn withdraw(&mut self, to: ActorId, amount: u128) {
+        let recipient = msg::source();
+        if let Some(&(_, prev_to, prev_amount)) = self.withdrawals.get(&recipient) {
+            // the previous withdrawal didn`t complete, it must be retried with the same arguments
+            if to != prev_to || amount != prev_amount {
+                msg::reply(
+                    StoreEvent::CompletePrevWithdrawal {
+                        to: prev_to,
+                        amount: prev_amount,
+                    },
+                    0,
+                )
+                .expect("Error in sending a reply `StoreEvent::CompletePrevWithdrawal`");
+                return;
+            }
+        } else {
+            let balance = self.balances.get(&recipient).copied().unwrap_or_default();
+            assert!(amount <= balance, "Not enough balance to withdraw");
+        }
+
+        let result = self.pay_out(recipient, to, amount).await;
+
+        msg::reply(StoreEvent::Withdrawn { success: result }, 0)
+            .expect("Error in sending a reply `StoreEvent::Withdrawn`");
+    }
+
+    // Transfers `amount` of the `recipient` balance to `to`. The transaction
+    // id is kept in `withdrawals` until the transfer completes.
+    async fn pay_out(&mut self, recipient: ActorId, to: ActorId, amount: u128) -> bool {
+        let transaction_id = if let Some(&(transaction_id, ..)) = self.withdrawals.get(&recipient) {
+            transaction_id
+        } else {
+            let current_transaction_id = self.next_transaction_id();
+            self.withdrawals
+                .insert(recipient, (current_transaction_id, to, amount));
+            current_transaction_id
+        };
+
+        let result = transfer_tokens(
+            transaction_id,
+            &self.ft_contract_id,
+            &exec::program_id(),
+            &to,
+            amount,
+        )
+        .await
+        .is_ok();
+
+        // the balance is debited once even if the same withdrawal was
+        // retried while the transfer was in progress
+        if self
+            .withdrawals
+            .get(&recipient)
+            .map_or(true, |&(current, ..)| current != transaction_id)
+        {
+            return result;
+        }
+        if result {
+            let balance = self.balances.entry(recipient).or_default();
+            *balance = balance
+                .checked_sub(amount)
+                .expect("Withdrawn more than the balance");
+        }
+        self.withdrawals.remove(&recipient);
+        result
+    }
+
+    fn next_transaction_id(&mut self) -> TransactionId {
+        let transaction_id = self.transaction_id;
+        self.transaction_id = self.transaction_id.wrapping_add(1);
+        transaction_id
+    }
+
+    // Sales paid with fungible tokens keep the id of their transfer, the
+    // others get a new one. Receipts are never removed, so they can back
+    // disputes.
+    fn record_sale(
+        &mut self,
+        transaction_id: Option<TransactionId>,
+        buyer: TamagotchiId,
+        purchase: Purchase,
+        price: Price,
+        currency: Currency,
+    ) {
+        let transaction_id = transaction_id.unwrap_or_else(|| self.next_transaction_id());
+        self.receipts.push(Receipt {
+            transaction_id,
+            buyer,
+            purchase,
+            price,
+            currency,
+            block: exec::block_height(),
+        });
+    }
+
+    // Returns the attribute price with the discount of a running sale applied.
+    fn discounted_price(&self, attribute_id: AttributeId, price: Price) -> Price {
+        let Some(sale) = self.sales.get(&attribute_id) else {
+            return price;
+        };
+        let block_height = exec::block_height();
+        if block_height < sale.start_block || block_height >= sale.end_block {
+            return price;
+        }
+
+        let percent_to_pay = u128::from(100 - sale.discount_percent);
+        price / 100 * percent_to_pay + price % 100 * percent_to_pay / 100
+    }
+
+    fn create_sale(
+        &mut self,
+        attribute_id: AttributeId,
+        start_block: u32,
+        end_block: u32,
+        discount_percent: u8,
+    ) {
+        self.access_control.check_role(Role::CatalogManager);
+        assert!(
+            self.attributes.contains_key(&attribute_id),
+            "Can`t get attribute_id"
+        );
+        assert!(start_block < end_block, "The sale must end after it starts");
+        assert!(
+            discount_percent <= 100,
+            "The discount can`t be more than 100 percent"
+        );
+
+        self.sales.insert(
+            attribute_id,
+            Sale {
+                start_block,
+                end_block,
+                discount_percent,
+            },
+        );
+        msg::reply(StoreEvent::SaleCreated { attribute_id }, 0)
+            .expect("Error in sending a reply `StoreEvent::SaleCreated`");
+    }
+
+    fn cancel_sale(&mut self, attribute_id: AttributeId) {
+        self.access_control.check_role(Role::CatalogManager);
+        self.sales.remove(&attribute_id);
+        msg::reply(StoreEvent::SaleCancelled { attribute_id }, 0)
+            .expect("Error in sending a reply `StoreEvent::SaleCancelled`");
+    }
+
+    fn create_auction(&mut self, attribute_id: AttributeId, min_bid: u128, duration: u32) {
+        self.access_control.check_role(Role::CatalogManager);
+        assert!(
+            self.attributes.contains_key(&attribute_id),
+            "Can`t get attribute_id"
+        );
+        assert!(
+            !self.auctions.contains_key(&attribute_id),
+            "The attribute has already been auctioned"
+        );
+        assert!(duration > 0, "The auction duration must be positive");
+
+        // the settlement is funded from a reservation made by this message,
+        // so it fires even if nobody touches the store after the auction ends
+        let reservation_id = ReservationId::reserve(GAS_FOR_SETTLEMENT, duration + 1)
+            .expect("Error in reserving gas for the auction settlement");
+        msg::send_delayed_from_reservation(
+            reservation_id,
+            exec::program_id(),
+            StoreAction::SettleAuction { attribute_id },
+            0,
+            duration,
+        )
+        .expect("Error in sending a delayed message `StoreAction::SettleAuction`");
+
+        let end_block = exec::block_height() + duration;
+        self.auctions.insert(
+            attribute_id,
+            Auction {
+                min_bid,
+                end_block,
+                highest_bid: None,
+                settled: false,
+            },
+        );
+        msg::reply(
+            StoreEvent::AuctionCreated {
+                attribute_id,
+                end_block,
+            },
+            0,
+        )
+        .expect("Error in sending a reply `StoreEvent::AuctionCreated`");
+    }
+
+    async fn bid(&mut self, attribute_id: AttributeId, amount: u128) {
+        assert!(!self.paused, "The store is paused");
+
+        let purchase = Purchase::Bid {
+            attribute_id,
+            amount,
+        };
+        let Some(pending) = self.start_transaction(purchase, amount).await else {
+            return;
+        };
+
+        if pending.retries == 0 {
+            let auction = self
+                .auctions
+                .get(&attribute_id)
+                .expect("The attribute isn`t auctioned");
+            assert!(is_open(auction), "The auction is over");
+            assert!(outbids(auction, amount), "The bid is too low");
+        }
+
+        let accepted = self.complete_transaction(msg::source(), pending).await;
+
+        msg::reply(StoreEvent::BidPlaced { success: accepted }, 0)
+            .expect("Error in sending a reply `StoreEvent::BidPlaced`");
+    }
+
+    // Places escrowed tokens as a bid. The auction could have been outbid or
+    // settled while the tokens were being escrowed, then the bid goes back
+    // to the bidder.
+    async fn place_bid(
+        &mut self,
+        bidder: ActorId,
+        attribute_id: AttributeId,
+        amount: u128,
+    ) -> bool {
+        let auction = self
+            .auctions
+            .get_mut(&attribute_id)
+            .expect("The attribute isn`t auctioned");
+        let accepted = is_open(auction) && outbids(auction, amount);
+        let refund = if accepted {
+            auction.highest_bid.replace((bidder, amount))
+        } else {
+            Some((bidder, amount))
+        };
+
+        if let Some((refund_to, refund_amount)) = refund {
+            self.refund(refund_to, refund_amount).await;
+        }
+        accepted
+    }
+
+    // Returns escrowed bid tokens. The amount is credited to the bidder
+    // balance first, so it can still be withdrawn if the transfer fails.
+    async fn refund(&mut self, bidder: ActorId, amount: u128) {
+        *self.balances.entry(bidder).or_default() += amount;
+        if !self.withdrawals.contains_key(&bidder) {
+            self.pay_out(bidder, bidder, amount).await;
+        }
+    }
+
+    fn settle_auction(&mut self, attribute_id: AttributeId) {
+        let auction = self
+            .auctions
+            .get_mut(&attribute_id)
+            .expect("The attribute isn`t auctioned");
+        // the auction was settled by hand before its delayed settlement
+        // arrived, the reserved gas the message doesn't use is returned
+        if auction.settled && msg::source() == exec::program_id() {
+            return;
+        }
+        assert!(!auction.settled, "The auction is already settled");
+        assert!(
+            exec::block_height() >= auction.end_block,
+            "The auction isn`t over yet"
+        );
+        auction.settled = true;
+
+        let highest_bid = auction.highest_bid;
+        if let Some((winner, amount)) = highest_bid {
+            self.distribute_revenue(amount, Currency::FungibleToken);
+            self.owners.entry(winner).or_default().insert(attribute_id);
+            self.record_sale(
+                None,
+                winner,
+                Purchase::Bid {
+                    attribute_id,
+                    amount,
+                },
+                amount,
+                Currency::FungibleToken,
+            );
+        }
+
+        msg::reply(
+            StoreEvent::AuctionSettled {
+                attribute_id,
+                winner: highest_bid.map(|(winner, _)| winner),
+            },
+            0,
+        )
+        .expect("Error in sending a reply `StoreEvent::AuctionSettled`");
+    }
+
+    fn set_native_price(&mut self, attribute_id: AttributeId, price: Option<Price>) {
+        self.access_control.check_role(Role::CatalogManager);
+        assert!(
+            self.attributes.contains_key(&attribute_id),
+            "Can`t get attribute_id"
+        );
+
+        match price {
+            Some(price) => self.native_prices.insert(attribute_id, price),
+            None => self.native_prices.remove(&attribute_id),
+        };
+        msg::reply(
+            StoreEvent::NativePriceSet {
+                attribute_id,
+                price,
+            },
+            0,
+        )
+        .expect("Error in sending a reply `StoreEvent::NativePriceSet`");
+    }
+
+    fn withdraw_native(&mut self, to: ActorId, amount: u128) {
+        let balance = self
+            .native_balances
+            .get_mut(&msg::source())
+            .expect("Not enough balance to withdraw");
+        assert!(amount <= *balance, "Not enough balance to withdraw");
+        *balance -= amount;
+
+        msg::send(to, StoreEvent::NativeWithdrawn { to, amount }, amount)
+            .expect("Error in sending a message `StoreEvent::NativeWithdrawn`");
+        msg::reply(StoreEvent::NativeWithdrawn { to, amount }, 0)
+            .expect("Error in sending a reply `StoreEvent::NativeWithdrawn`");
+    }
+
+    fn grant_role(&mut self, role: Role, account: ActorId) {
+        self.access_control.grant_role(role, account);
+        msg::reply(StoreEvent::RoleGranted { role, account }, 0)
+            .expect("Error in sending a reply `StoreEvent::RoleGranted`");
+    }
+
+    fn revoke_role(&mut self, role: Role, account: ActorId) {
+        self.access_control.revoke_role(role, account);
+        msg::reply(StoreEvent::RoleRevoked { role, account }, 0)
+            .expect("Error in sending a reply `StoreEvent::RoleRevoked`");
+    }
+
+    fn transfer_ownership(&mut self, new_owner: ActorId) {
+        self.access_control.transfer_ownership(new_owner);
+        msg::reply(StoreEvent::OwnershipTransferStarted { new_owner }, 0)
+            .expect("Error in sending a reply `StoreEvent::OwnershipTransferStarted`");
+    }
+
+    fn accept_ownership(&mut self) {
+        self.access_control.accept_ownership();
+        msg::reply(
+            StoreEvent::OwnershipTransferred {
+                new_owner: self.access_control.owner,
+            },
+            0,
+        )
+        .expect("Error in sending a reply `StoreEvent::OwnershipTransferred`");
+    }
+
+    fn set_paused(&mut self, paused: bool) {
+        self.access_control.check_role(Role::Pauser);
+        self.paused = paused;
+        let event = if paused {
+            StoreEvent::Paused
+        } else {
+            StoreEvent::Unpaused
+        };
+        msg::reply(event, 0).expect("Error in sending a reply `StoreEvent::Paused`");
+    }
 }
 
-#[gstd::async_main]
+#[gstd::async_main(handle_signal = signal)]
 async fn main() {
     let action: StoreAction = msg::load().expect("Unable to decode `StoreAction");
     let store: &mut AttributeStore =
@@ -157,11 +903,57 @@ async fn main() {
             price,
         } => store.create_attribute(attribute_id, &attr_metadata, price),
         StoreAction::BuyAttribute { attribute_id } => store.buy_attribute(attribute_id).await,
+        StoreAction::CreateBundle {
+            bundle_id,
+            attributes,
+            price,
+            supply,
+        } => store.create_bundle(bundle_id, attributes, price, supply),
+        StoreAction::BuyBundle { bundle_id } => store.buy_bundle(bundle_id).await,
         StoreAction::GetAttributes { tamagotchi_id } => store.get_attributes(&tamagotchi_id),
+        StoreAction::GetAttributePrice { attribute_id } => store.get_attribute_price(attribute_id),
         StoreAction::SetFtContractId { ft_contract_id } => {
             store.set_ft_contract_id(&ft_contract_id)
         }
         StoreAction::RemoveTx { tamagotchi_id } => store.remove_tx(&tamagotchi_id),
+        StoreAction::RecoverTx { tamagotchi_id } => store.recover_tx(tamagotchi_id).await,
+        StoreAction::GetStuckTxs => store.get_stuck_txs(),
+        StoreAction::SetRevenueSplit { recipients } => store.set_revenue_split(recipients),
+        StoreAction::Withdraw { to, amount } => store.withdraw(to, amount).await,
+        StoreAction::SetNativePrice {
+            attribute_id,
+            price,
+        } => store.set_native_price(attribute_id, price),
+        StoreAction::WithdrawNative { to, amount } => store.withdraw_native(to, amount),
+        StoreAction::CreateSale {
+            attribute_id,
+            start_block,
+            end_block,
+            discount_percent,
+        } => store.create_sale(attribute_id, start_block, end_block, discount_percent),
+        StoreAction::CancelSale { attribute_id } => store.cancel_sale(attribute_id),
+        StoreAction::CreateAuction {
+            attribute_id,
+            min_bid,
+            duration,
+        } => store.create_auction(attribute_id, min_bid, duration),
+        StoreAction::Bid {
+            attribute_id,
+            amount,
+        } => store.bid(attribute_id, amount).await,
+        StoreAction::TransferAttribute { attribute_id, to } => {
+            store.transfer_attribute(attribute_id, to)
+        }
+        StoreAction::GiftAttribute { attribute_id, to } => {
+            store.gift_attribute(attribute_id, to).await
+        }
+        StoreAction::SettleAuction { attribute_id } => store.settle_auction(attribute_id),
+        StoreAction::GrantRole { role, account } => store.grant_role(role, account),
+        StoreAction::RevokeRole { role, account } => store.revoke_role(role, account),
+        StoreAction::TransferOwnership { new_owner } => store.transfer_ownership(new_owner),
+        StoreAction::AcceptOwnership => store.accept_ownership(),
+        StoreAction::Pause => store.set_paused(true),
+        StoreAction::Unpause => store.set_paused(false),
     }
 }
 
@@ -169,13 +961,48 @@ async fn main() {
 extern fn init() {
     let ft_contract_id: ActorId = msg::load().expect("Unable to decode `ActorId`");
     let store = AttributeStore {
-        admin: msg::source(),
+        access_control: AccessControl::new(msg::source()),
         ft_contract_id,
         ..Default::default()
     };
     unsafe { STORE = Some(store) };
 }
 
+fn notify_recipient(attribute_id: AttributeId, from: TamagotchiId, to: TamagotchiId) {
+    msg::send(to, TmgAction::AttributeReceived { attribute_id, from }, 0)
+        .expect("Error in sending a message `TmgAction::AttributeReceived`");
+}
+
+// The failed message can't complete its transaction anymore, it's marked so
+// that it can be recovered right away.
+fn signal() {
+    let store = unsafe { STORE.as_mut().expect("The contract is not initialized") };
+    let failed = msg::signal_from().expect("Error in getting the failed message");
+    if let Some(pending) = store
+        .transactions
+        .values_mut()
+        .find(|pending| pending.message_id == failed)
+    {
+        pending.interrupted = true;
+    }
+}
+
+fn is_stuck(pending: &PendingTx) -> bool {
+    pending.interrupted
+        || exec::block_height() >= pending.created_at.saturating_add(TX_EXPIRATION_BLOCKS)
+}
+
+fn is_open(auction: &Auction) -> bool {
+    !auction.settled && exec::block_height() < auction.end_block
+}
+
+fn outbids(auction: &Auction, amount: u128) -> bool {
+    amount >= auction.min_bid
+        && auction
+            .highest_bid
+            .map_or(true, |(_, highest_bid)| amount > highest_bid)
+}
+
 async fn transfer_tokens(
     transaction_id: TransactionId,
     token_address: &ActorId,
@@ -211,12 +1038,22 @@ extern fn state() {
 
     msg::reply(
         store_io::AttributeStore {
-            admin: store.admin,
+            access_control: store.access_control.clone(),
+            paused: store.paused,
             ft_contract_id: store.ft_contract_id,
             attributes: store.attributes.clone(),
             owners: store.owners.clone(),
             transaction_id: store.transaction_id,
             transactions: store.transactions.clone(),
+            revenue_split: store.revenue_split.clone(),
+            balances: store.balances.clone(),
+            withdrawals: store.withdrawals.clone(),
+            bundles: store.bundles.clone(),
+            native_prices: store.native_prices.clone(),
+            native_balances: store.native_balances.clone(),
+            sales: store.sales.clone(),
+            auctions: store.auctions.clone(),
+            receipts: store.receipts.clone(),
         },
         0,
     )
@@ -0,0 +1,79 @@
+#![no_std]
+use gmeta::{metawasm, Metadata};
+#[allow(unused_imports)]
+use gstd::{prelude::*, ActorId, Vec};
+use store_io::{AttributeId, AttributeStore, ProgramMetadata, Purchase, Receipt};
+
+#[metawasm]
+pub mod metafns {
+    pub type State = <ProgramMetadata as Metadata>::State;
+
+    pub fn receipts_count(state: State) -> u64 {
+        state.receipts.len() as u64
+    }
+
+    /// The receipt with the sequence number `index`.
+    pub fn receipt(state: State, index: u64) -> Option<Receipt> {
+        state.receipts.get(index as usize).copied()
+    }
+
+    /// Receipts in the order of sale, `limit` of them starting at `offset`.
+    pub fn receipts(state: State, offset: u64, limit: u64) -> Vec<Receipt> {
+        page(state.receipts.iter(), offset, limit)
+    }
+
+    pub fn receipts_of_buyer(
+        state: State,
+        buyer: ActorId,
+        offset: u64,
+        limit: u64,
+    ) -> Vec<Receipt> {
+        let receipts = state
+            .receipts
+            .iter()
+            .filter(|receipt| receipt.buyer == buyer);
+        page(receipts, offset, limit)
+    }
+
+    /// Receipts of sales that included `attribute_id`, bundles among them.
+    pub fn receipts_of_attribute(
+        state: State,
+        attribute_id: AttributeId,
+        offset: u64,
+        limit: u64,
+    ) -> Vec<Receipt> {
+        let receipts = state
+            .receipts
+            .iter()
+            .filter(|receipt| includes_attribute(&state, receipt, attribute_id));
+        page(receipts, offset, limit)
+    }
+}
+
+fn page<'a>(receipts: impl Iterator<Item = &'a Receipt>, offset: u64, limit: u64) -> Vec<Receipt> {
+    receipts
+        .skip(offset as usize)
+        .take(limit as usize)
+        .copied()
+        .collect()
+}
+
+fn includes_attribute(
+    state: &AttributeStore,
+    receipt: &Receipt,
+    attribute_id: AttributeId,
+) -> bool {
+    match receipt.purchase {
+        Purchase::Attribute(id)
+        | Purchase::Gift {
+            attribute_id: id, ..
+        }
+        | Purchase::Bid {
+            attribute_id: id, ..
+        } => id == attribute_id,
+        Purchase::Bundle(bundle_id) => state
+            .bundles
+            .get(&bundle_id)
+            .is_some_and(|bundle| bundle.attributes.contains(&attribute_id)),
+    }
+}
@@ -0,0 +1,149 @@
+mod common;
+
+use common::{
+    actor_id, check_ft_balance, init_ft, init_store, mint_and_approve, store_state, ADMIN, PRICE,
+    SWORD_ID, USER,
+};
+use gtest::{Log, System};
+use store_io::{StoreAction, StoreEvent};
+
+const OTHER_USER: u64 = 102;
+const MIN_BID: u128 = PRICE;
+const DURATION: u32 = 10;
+
+#[test]
+fn english_auction() {
+    let sys = System::new();
+    sys.init_logger();
+    let ft = init_ft(&sys);
+    let store = init_store(&sys, actor_id(&ft));
+    let store_id = actor_id(&store);
+
+    // must fail since only a catalog manager creates auctions
+    let create_auction = StoreAction::CreateAuction {
+        attribute_id: SWORD_ID,
+        min_bid: MIN_BID,
+        duration: DURATION,
+    };
+    let res = store.send(USER, create_auction);
+    assert!(res.main_failed());
+
+    let res = store.send(
+        ADMIN,
+        StoreAction::CreateAuction {
+            attribute_id: SWORD_ID,
+            min_bid: MIN_BID,
+            duration: DURATION,
+        },
+    );
+    assert!(!res.main_failed());
+
+    // an auctioned attribute can only be won
+    let res = store.send(
+        USER,
+        StoreAction::BuyAttribute {
+            attribute_id: SWORD_ID,
+        },
+    );
+    assert!(res.main_failed());
+    let res = store.send(
+        ADMIN,
+        StoreAction::CreateBundle {
+            bundle_id: 1,
+            attributes: [SWORD_ID].into(),
+            price: PRICE,
+            supply: None,
+        },
+    );
+    assert!(res.main_failed());
+
+    mint_and_approve(&ft, USER, store_id, MIN_BID, 0);
+    mint_and_approve(&ft, OTHER_USER, store_id, MIN_BID + 1, 0);
+
+    // must fail since the bid is below the minimum
+    let res = store.send(
+        USER,
+        StoreAction::Bid {
+            attribute_id: SWORD_ID,
+            amount: MIN_BID - 1,
+        },
+    );
+    assert!(res.main_failed());
+
+    let res = store.send(
+        USER,
+        StoreAction::Bid {
+            attribute_id: SWORD_ID,
+            amount: MIN_BID,
+        },
+    );
+    let log = Log::builder()
+        .dest(USER)
+        .payload(StoreEvent::BidPlaced { success: true });
+    assert!(res.contains(&log));
+    check_ft_balance(&ft, store_id, MIN_BID);
+
+    // the outbid tokens go back to USER
+    let res = store.send(
+        OTHER_USER,
+        StoreAction::Bid {
+            attribute_id: SWORD_ID,
+            amount: MIN_BID + 1,
+        },
+    );
+    let log = Log::builder()
+        .dest(OTHER_USER)
+        .payload(StoreEvent::BidPlaced { success: true });
+    assert!(res.contains(&log));
+    check_ft_balance(&ft, USER.into(), MIN_BID);
+    check_ft_balance(&ft, store_id, MIN_BID + 1);
+
+    // must fail since the auction isn't over yet
+    let res = store.send(
+        USER,
+        StoreAction::SettleAuction {
+            attribute_id: SWORD_ID,
+        },
+    );
+    assert!(res.main_failed());
+
+    // the delayed settlement hands the attribute to the highest bidder
+    sys.spend_blocks(DURATION);
+    let state = store_state(&store);
+    assert!(state.auctions[&SWORD_ID].settled);
+    assert_eq!(state.owners[&OTHER_USER.into()], [SWORD_ID].into());
+    assert_eq!(state.balances[&ADMIN.into()], MIN_BID + 1);
+
+    let res = store.send(
+        USER,
+        StoreAction::SettleAuction {
+            attribute_id: SWORD_ID,
+        },
+    );
+    assert!(res.main_failed());
+}
+
+#[test]
+fn auction_without_bids() {
+    let sys = System::new();
+    sys.init_logger();
+    let ft = init_ft(&sys);
+    let store = init_store(&sys, actor_id(&ft));
+
+    let res = store.send(
+        ADMIN,
+        StoreAction::CreateAuction {
+            attribute_id: SWORD_ID,
+            min_bid: MIN_BID,
+            duration: DURATION,
+        },
+    );
+    assert!(!res.main_failed());
+
+    sys.spend_blocks(DURATION);
+    let state = store_state(&store);
+    let auction = &state.auctions[&SWORD_ID];
+    assert!(auction.settled);
+    assert_eq!(auction.highest_bid, None);
+    assert!(state.owners.is_empty());
+}
@@ -0,0 +1,96 @@
+mod common;
+
+use common::{
+    actor_id, create_attribute, init_ft, init_store, mint_and_approve, store_state, ADMIN, PRICE,
+    SWORD_ID, USER,
+};
+use gtest::{Log, System};
+use store_io::{AttributeId, BundleId, StoreAction, StoreEvent};
+
+const OTHER_USER: u64 = 102;
+const SHIELD_ID: AttributeId = 2;
+const BUNDLE_ID: BundleId = 1;
+const BUNDLE_PRICE: u128 = PRICE;
+
+#[test]
+fn buy_bundle() {
+    let sys = System::new();
+    sys.init_logger();
+    let ft = init_ft(&sys);
+    let store = init_store(&sys, actor_id(&ft));
+    let store_id = actor_id(&store);
+    create_attribute(&store, SHIELD_ID, PRICE);
+
+    let create_bundle = |attributes: &[AttributeId]| StoreAction::CreateBundle {
+        bundle_id: BUNDLE_ID,
+        attributes: attributes.iter().copied().collect(),
+        price: BUNDLE_PRICE,
+        supply: Some(1),
+    };
+
+    // must fail since only a catalog manager creates bundles
+    let res = store.send(USER, create_bundle(&[SWORD_ID, SHIELD_ID]));
+    assert!(res.main_failed());
+
+    // must fail since the bundle contains an attribute that doesn't exist
+    let res = store.send(ADMIN, create_bundle(&[SWORD_ID, 3]));
+    assert!(res.main_failed());
+
+    let res = store.send(ADMIN, create_bundle(&[SWORD_ID, SHIELD_ID]));
+    let log = Log::builder()
+        .dest(ADMIN)
+        .payload(StoreEvent::BundleCreated {
+            bundle_id: BUNDLE_ID,
+        });
+    assert!(res.contains(&log));
+
+    // the transfer fails without an approval, the unit reserved by the
+    // purchase is released and nothing is granted
+    let res = store.send(
+        USER,
+        StoreAction::BuyBundle {
+            bundle_id: BUNDLE_ID,
+        },
+    );
+    let log = Log::builder()
+        .dest(USER)
+        .payload(StoreEvent::BundleSold { success: false });
+    assert!(res.contains(&log));
+    let state = store_state(&store);
+    assert_eq!(state.bundles[&BUNDLE_ID].sold, 0);
+    assert!(!state.owners.contains_key(&USER.into()));
+
+    mint_and_approve(&ft, USER, store_id, BUNDLE_PRICE, 0);
+    let res = store.send(
+        USER,
+        StoreAction::BuyBundle {
+            bundle_id: BUNDLE_ID,
+        },
+    );
+    let log = Log::builder()
+        .dest(USER)
+        .payload(StoreEvent::BundleSold { success: true });
+    assert!(res.contains(&log));
+
+    let res = store.send(
+        USER,
+        StoreAction::GetAttributes {
+            tamagotchi_id: USER.into(),
+        },
+    );
+    let log = Log::builder().dest(USER).payload(StoreEvent::Attributes {
+        attributes: [SWORD_ID, SHIELD_ID].into(),
+    });
+    assert!(res.contains(&log));
+
+    // must fail since the only unit is sold
+    mint_and_approve(&ft, OTHER_USER, store_id, BUNDLE_PRICE, 0);
+    let res = store.send(
+        OTHER_USER,
+        StoreAction::BuyBundle {
+            bundle_id: BUNDLE_ID,
+        },
+    );
+    assert!(res.main_failed());
+    assert_eq!(store_state(&store).bundles[&BUNDLE_ID].sold, 1);
+}
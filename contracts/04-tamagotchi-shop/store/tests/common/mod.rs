@@ -0,0 +1,114 @@
+#![allow(dead_code)]
+
+use gstd::ActorId;
+use gtest::{Log, Program, System};
+use sharded_fungible_token_io::{FTokenAction, FTokenEvent, InitFToken, LogicAction};
+use store_io::{AttrMetadata, AttributeId, AttributeStore, StoreAction, StoreEvent};
+
+pub const ADMIN: u64 = 100;
+pub const USER: u64 = 101;
+pub const SWORD_ID: AttributeId = 1;
+pub const PRICE: u128 = 15_000_000_000_000;
+
+const FT_STORAGE_WASM: &str =
+    "../../target/wasm32-unknown-unknown/release/sharded_fungible_token_storage.opt.wasm";
+const FT_LOGIC_WASM: &str =
+    "../../target/wasm32-unknown-unknown/release/sharded_fungible_token_logic.opt.wasm";
+const FT_MAIN_WASM: &str =
+    "../../target/wasm32-unknown-unknown/release/sharded_fungible_token.opt.wasm";
+
+pub fn actor_id(program: &Program<'_>) -> ActorId {
+    ActorId::new(program.id().into_bytes())
+}
+
+/// Initializes the store with a sword priced at [`PRICE`].
+pub fn init_store(sys: &System, ft_contract_id: ActorId) -> Program<'_> {
+    let store = Program::current(sys);
+    let res = store.send(ADMIN, ft_contract_id);
+    assert!(!res.main_failed());
+
+    create_attribute(&store, SWORD_ID, PRICE);
+
+    store
+}
+
+pub fn create_attribute(store: &Program<'_>, attribute_id: AttributeId, price: u128) {
+    let res = store.send(
+        ADMIN,
+        StoreAction::CreateAttribute {
+            attribute_id,
+            attr_metadata: AttrMetadata {
+                title: String::from("Sword"),
+                description: String::from("sword"),
+                media: String::from("sword"),
+            },
+            price,
+        },
+    );
+    let log = Log::builder()
+        .dest(ADMIN)
+        .payload(StoreEvent::AttributeCreated { attribute_id });
+    assert!(res.contains(&log));
+}
+
+pub fn init_ft(sys: &System) -> Program<'_> {
+    let storage_code_id = sys.submit_code(FT_STORAGE_WASM);
+    let logic_code_id = sys.submit_code(FT_LOGIC_WASM);
+    let ft = Program::from_file(sys, FT_MAIN_WASM);
+    let res = ft.send(
+        ADMIN,
+        InitFToken {
+            storage_code_hash: storage_code_id.into_bytes().into(),
+            ft_logic_code_hash: logic_code_id.into_bytes().into(),
+        },
+    );
+    assert!(!res.main_failed());
+
+    ft
+}
+
+/// Mints `amount` tokens to `account` and approves them to `spender`. The
+/// approval uses `transaction_id + 1`.
+pub fn mint_and_approve(
+    ft: &Program<'_>,
+    account: u64,
+    spender: ActorId,
+    amount: u128,
+    transaction_id: u64,
+) {
+    let res = ft.send(
+        account,
+        FTokenAction::Message {
+            transaction_id,
+            payload: LogicAction::Mint {
+                recipient: account.into(),
+                amount,
+            },
+        },
+    );
+    assert!(!res.main_failed());
+
+    let res = ft.send(
+        account,
+        FTokenAction::Message {
+            transaction_id: transaction_id + 1,
+            payload: LogicAction::Approve {
+                approved_account: spender,
+                amount,
+            },
+        },
+    );
+    assert!(!res.main_failed());
+}
+
+pub fn check_ft_balance(ft: &Program<'_>, account: ActorId, balance: u128) {
+    let res = ft.send(ADMIN, FTokenAction::GetBalance(account));
+    let log = Log::builder()
+        .dest(ADMIN)
+        .payload(FTokenEvent::Balance(balance));
+    assert!(res.contains(&log));
+}
+
+pub fn store_state(store: &Program<'_>) -> AttributeStore {
+    store.read_state(b"").expect("Unable to read the state")
+}
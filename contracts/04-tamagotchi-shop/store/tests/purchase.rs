@@ -0,0 +1,209 @@
+mod common;
+
+use common::{
+    actor_id, check_ft_balance, init_ft, init_store, mint_and_approve, store_state, ADMIN, PRICE,
+    SWORD_ID, USER,
+};
+use gstd::ActorId;
+use gtest::{Log, System};
+use store_io::{StoreAction, StoreEvent};
+
+const ARTIST: u64 = 102;
+const TREASURY: u64 = 103;
+const ONE_VARA: u128 = 1_000_000_000_000;
+
+#[test]
+fn buy_attribute_with_native_value() {
+    let sys = System::new();
+    sys.init_logger();
+    let store = init_store(&sys, ActorId::zero());
+
+    // the attribute has no native price yet
+    sys.mint_to(USER, PRICE * 2 + ONE_VARA * 2);
+    let res = store.send_with_value(
+        USER,
+        StoreAction::BuyAttribute {
+            attribute_id: SWORD_ID,
+        },
+        PRICE,
+    );
+    assert!(res.main_failed());
+
+    let res = store.send(
+        ADMIN,
+        StoreAction::SetNativePrice {
+            attribute_id: SWORD_ID,
+            price: Some(PRICE),
+        },
+    );
+    assert!(!res.main_failed());
+
+    // must fail since USER attaches not enough value
+    let res = store.send_with_value(
+        USER,
+        StoreAction::BuyAttribute {
+            attribute_id: SWORD_ID,
+        },
+        PRICE - ONE_VARA,
+    );
+    assert!(res.main_failed());
+
+    // the overpayment is returned with the reply
+    let res = store.send_with_value(
+        USER,
+        StoreAction::BuyAttribute {
+            attribute_id: SWORD_ID,
+        },
+        PRICE + ONE_VARA,
+    );
+    let log = Log::builder()
+        .dest(USER)
+        .payload(StoreEvent::AttributeSoldForValue { change: ONE_VARA });
+    assert!(res.contains(&log));
+    assert_eq!(sys.balance_of(store.id()), PRICE);
+
+    let res = store.send(
+        USER,
+        StoreAction::GetAttributes {
+            tamagotchi_id: USER.into(),
+        },
+    );
+    let log = Log::builder().dest(USER).payload(StoreEvent::Attributes {
+        attributes: [SWORD_ID].into(),
+    });
+    assert!(res.contains(&log));
+
+    // the whole revenue belongs to the owner until a split is configured
+    let res = store.send(
+        ADMIN,
+        StoreAction::WithdrawNative {
+            to: ADMIN.into(),
+            amount: PRICE,
+        },
+    );
+    assert!(!res.main_failed());
+    sys.claim_value_from_mailbox(ADMIN);
+    assert_eq!(sys.balance_of(ADMIN), PRICE);
+}
+
+#[test]
+fn buy_attribute_with_fungible_tokens() {
+    let sys = System::new();
+    sys.init_logger();
+    let ft = init_ft(&sys);
+    let store = init_store(&sys, actor_id(&ft));
+    let store_id = actor_id(&store);
+
+    mint_and_approve(&ft, USER, store_id, PRICE, 0);
+
+    let res = store.send(
+        USER,
+        StoreAction::BuyAttribute {
+            attribute_id: SWORD_ID,
+        },
+    );
+    let log = Log::builder()
+        .dest(USER)
+        .payload(StoreEvent::AttributeSold { success: true });
+    assert!(res.contains(&log));
+
+    check_ft_balance(&ft, store_id, PRICE);
+
+    let res = store.send(
+        ADMIN,
+        StoreAction::Withdraw {
+            to: ADMIN.into(),
+            amount: PRICE,
+        },
+    );
+    let log = Log::builder()
+        .dest(ADMIN)
+        .payload(StoreEvent::Withdrawn { success: true });
+    assert!(res.contains(&log));
+
+    check_ft_balance(&ft, ADMIN.into(), PRICE);
+}
+
+#[test]
+fn revenue_is_split_and_credited_once() {
+    let sys = System::new();
+    sys.init_logger();
+    let ft = init_ft(&sys);
+    let store = init_store(&sys, actor_id(&ft));
+    let store_id = actor_id(&store);
+
+    // the shares must add up to 100%
+    let res = store.send(
+        ADMIN,
+        StoreAction::SetRevenueSplit {
+            recipients: vec![(ARTIST.into(), 7_000), (TREASURY.into(), 2_000)],
+        },
+    );
+    assert!(res.main_failed());
+
+    let res = store.send(
+        ADMIN,
+        StoreAction::SetRevenueSplit {
+            recipients: vec![(ARTIST.into(), 7_000), (TREASURY.into(), 3_000)],
+        },
+    );
+    assert!(!res.main_failed());
+
+    mint_and_approve(&ft, USER, store_id, PRICE, 0);
+
+    let res = store.send(
+        USER,
+        StoreAction::BuyAttribute {
+            attribute_id: SWORD_ID,
+        },
+    );
+    let log = Log::builder()
+        .dest(USER)
+        .payload(StoreEvent::AttributeSold { success: true });
+    assert!(res.contains(&log));
+
+    let artist_share = PRICE / 10 * 7;
+    let treasury_share = PRICE / 10 * 3;
+    let state = store_state(&store);
+    assert_eq!(state.balances[&ARTIST.into()], artist_share);
+    assert_eq!(state.balances[&TREASURY.into()], treasury_share);
+
+    // the completed transaction can't be recovered and credited again
+    let res = store.send(
+        ADMIN,
+        StoreAction::RecoverTx {
+            tamagotchi_id: USER.into(),
+        },
+    );
+    assert!(res.main_failed());
+    let state = store_state(&store);
+    assert_eq!(state.balances[&ARTIST.into()], artist_share);
+
+    // must fail since ARTIST withdraws more than its share
+    let res = store.send(
+        ARTIST,
+        StoreAction::Withdraw {
+            to: ARTIST.into(),
+            amount: artist_share + 1,
+        },
+    );
+    assert!(res.main_failed());
+
+    let res = store.send(
+        ARTIST,
+        StoreAction::Withdraw {
+            to: ARTIST.into(),
+            amount: artist_share,
+        },
+    );
+    let log = Log::builder()
+        .dest(ARTIST)
+        .payload(StoreEvent::Withdrawn { success: true });
+    assert!(res.contains(&log));
+
+    let state = store_state(&store);
+    assert_eq!(state.balances[&ARTIST.into()], 0);
+    assert_eq!(state.balances[&TREASURY.into()], treasury_share);
+
+    check_ft_balance(&ft, ARTIST.into(), artist_share);
+}
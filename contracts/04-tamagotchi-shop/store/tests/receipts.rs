@@ -0,0 +1,88 @@
+mod common;
+
+use common::{
+    actor_id, init_ft, init_store, mint_and_approve, store_state, ADMIN, PRICE, SWORD_ID, USER,
+};
+use gstd::ActorId;
+use gtest::System;
+use store_io::{Currency, Purchase, StoreAction};
+
+#[test]
+fn every_receipt_has_a_transaction_id() {
+    let sys = System::new();
+    sys.init_logger();
+    let ft = init_ft(&sys);
+    let store = init_store(&sys, actor_id(&ft));
+    mint_and_approve(&ft, USER, actor_id(&store), PRICE, 0);
+
+    let res = store.send(
+        USER,
+        StoreAction::BuyAttribute {
+            attribute_id: SWORD_ID,
+        },
+    );
+    assert!(!res.main_failed());
+
+    let res = store.send(
+        ADMIN,
+        StoreAction::SetNativePrice {
+            attribute_id: SWORD_ID,
+            price: Some(PRICE),
+        },
+    );
+    assert!(!res.main_failed());
+    sys.mint_to(USER, PRICE);
+    let res = store.send_with_value(
+        USER,
+        StoreAction::BuyAttribute {
+            attribute_id: SWORD_ID,
+        },
+        PRICE,
+    );
+    assert!(!res.main_failed());
+
+    let state = store_state(&store);
+    assert_eq!(state.receipts.len(), 2);
+    let (ft_receipt, native_receipt) = (&state.receipts[0], &state.receipts[1]);
+    assert_eq!(ft_receipt.currency, Currency::FungibleToken);
+    assert_eq!(ft_receipt.purchase, Purchase::Attribute(SWORD_ID));
+    assert_eq!(native_receipt.currency, Currency::Native);
+    assert_ne!(ft_receipt.transaction_id, native_receipt.transaction_id);
+}
+
+#[test]
+fn receipts_are_never_dropped() {
+    let sys = System::new();
+    sys.init_logger();
+    let store = init_store(&sys, ActorId::zero());
+    let res = store.send(
+        ADMIN,
+        StoreAction::SetNativePrice {
+            attribute_id: SWORD_ID,
+            price: Some(PRICE),
+        },
+    );
+    assert!(!res.main_failed());
+
+    let sales = 5;
+    sys.mint_to(USER, PRICE * sales as u128);
+    for _ in 0..sales {
+        let res = store.send_with_value(
+            USER,
+            StoreAction::BuyAttribute {
+                attribute_id: SWORD_ID,
+            },
+            PRICE,
+        );
+        assert!(!res.main_failed());
+    }
+
+    // every sale is logged in order, from the first one
+    let state = store_state(&store);
+    let transaction_ids: Vec<_> = state
+        .receipts
+        .iter()
+        .map(|receipt| receipt.transaction_id)
+        .collect();
+    assert_eq!(transaction_ids, (0..sales).collect::<Vec<_>>());
+}
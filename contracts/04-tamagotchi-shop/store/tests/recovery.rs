@@ -0,0 +1,84 @@
+mod common;
+
+use common::{create_attribute, init_store, store_state, ADMIN, PRICE, SWORD_ID, USER};
+use gtest::{Log, System};
+use store_io::{AttributeId, BundleId, Purchase, StoreAction, StoreEvent};
+
+// An account instead of a program, the transfers sent to it never get a
+// reply, so the transactions stay pending.
+const UNRESPONSIVE_FT: u64 = 300;
+const SHIELD_ID: AttributeId = 2;
+const BUNDLE_ID: BundleId = 1;
+
+#[test]
+fn pending_bundle_tx_is_removed() {
+    let sys = System::new();
+    sys.init_logger();
+    let store = init_store(&sys, UNRESPONSIVE_FT.into());
+    create_attribute(&store, SHIELD_ID, PRICE);
+
+    let res = store.send(
+        ADMIN,
+        StoreAction::CreateBundle {
+            bundle_id: BUNDLE_ID,
+            attributes: [SWORD_ID, SHIELD_ID].into(),
+            price: PRICE,
+            supply: Some(1),
+        },
+    );
+    assert!(!res.main_failed());
+
+    let res = store.send(
+        USER,
+        StoreAction::BuyBundle {
+            bundle_id: BUNDLE_ID,
+        },
+    );
+    assert!(!res.main_failed());
+    let state = store_state(&store);
+    assert_eq!(
+        state.transactions[&USER.into()].purchase,
+        Purchase::Bundle(BUNDLE_ID)
+    );
+    assert_eq!(state.bundles[&BUNDLE_ID].sold, 1);
+
+    // the transaction is still being processed, so it isn't stuck and
+    // can't be recovered even by the owner
+    let res = store.send(ADMIN, StoreAction::GetStuckTxs);
+    let log = Log::builder().dest(ADMIN).payload(StoreEvent::StuckTxs {
+        transactions: vec![],
+    });
+    assert!(res.contains(&log));
+    let res = store.send(
+        ADMIN,
+        StoreAction::RecoverTx {
+            tamagotchi_id: USER.into(),
+        },
+    );
+    assert!(res.main_failed());
+
+    // must fail since only the owner removes transactions
+    let res = store.send(
+        USER,
+        StoreAction::RemoveTx {
+            tamagotchi_id: USER.into(),
+        },
+    );
+    assert!(res.main_failed());
+
+    let res = store.send(
+        ADMIN,
+        StoreAction::RemoveTx {
+            tamagotchi_id: USER.into(),
+        },
+    );
+    let log = Log::builder().dest(ADMIN).payload(StoreEvent::TxRemoved {
+        tamagotchi_id: USER.into(),
+    });
+    assert!(res.contains(&log));
+
+    // the unit reserved by the removed purchase can be sold again
+    let state = store_state(&store);
+    assert!(state.transactions.is_empty());
+    assert_eq!(state.bundles[&BUNDLE_ID].sold, 0);
+}
@@ -0,0 +1,58 @@
+mod common;
+
+use common::{actor_id, init_store, store_state, ADMIN, SWORD_ID, USER};
+use gstd::ActorId;
+use gtest::{Log, Program, System};
+use store_io::{StoreAction, StoreEvent};
+
+// The fungible token contract is another store, it can't decode
+// `FTokenAction`, so every transfer fails.
+fn init_broken_ft(sys: &System) -> Program<'_> {
+    let broken_ft = Program::current(sys);
+    let res = broken_ft.send(ADMIN, ActorId::zero());
+    assert!(!res.main_failed());
+
+    broken_ft
+}
+
+#[test]
+fn purchase_out_of_gas_is_marked_interrupted() {
+    let sys = System::new();
+    sys.init_logger();
+    let broken_ft = init_broken_ft(&sys);
+    let store = init_store(&sys, actor_id(&broken_ft));
+
+    // wherever the gas runs out, a transaction left pending is marked as
+    // interrupted and the buyer can recover it without waiting
+    let mut interrupted = 0;
+    for gas_limit in (1..=20).map(|i| i * 500_000_000) {
+        store.send_with_gas(
+            USER,
+            StoreAction::BuyAttribute {
+                attribute_id: SWORD_ID,
+            },
+            gas_limit,
+            0,
+        );
+
+        let state = store_state(&store);
+        let Some(pending) = state.transactions.get(&USER.into()) else {
+            continue;
+        };
+        assert!(pending.interrupted);
+        interrupted += 1;
+
+        let res = store.send(
+            USER,
+            StoreAction::RecoverTx {
+                tamagotchi_id: USER.into(),
+            },
+        );
+        let log = Log::builder().dest(USER).payload(StoreEvent::TxRecovered {
+            tamagotchi_id: USER.into(),
+            completed: false,
+        });
+        assert!(res.contains(&log));
+    }
+    assert!(interrupted > 0, "No purchase was interrupted");
+}
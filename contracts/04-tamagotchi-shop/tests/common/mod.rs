@@ -0,0 +1,108 @@
+#![allow(dead_code)]
+
+use gstd::ActorId;
+use gtest::{Log, Program, System};
+use sharded_fungible_token_io::{FTokenAction, FTokenEvent, InitFToken, LogicAction};
+use store_io::{AttrMetadata, AttributeId, StoreAction, StoreEvent};
+use tamagotchi_shop_io::{TmgAction, TmgEvent};
+
+pub const ADMIN: u64 = 100;
+pub const OWNER: u64 = 101;
+pub const USER: u64 = 102;
+pub const SWORD_ID: AttributeId = 1;
+pub const PRICE: u128 = 15_000_000_000_000;
+
+const STORE_WASM: &str = "../target/wasm32-unknown-unknown/release/store.opt.wasm";
+const FT_STORAGE_WASM: &str =
+    "../target/wasm32-unknown-unknown/release/sharded_fungible_token_storage.opt.wasm";
+const FT_LOGIC_WASM: &str =
+    "../target/wasm32-unknown-unknown/release/sharded_fungible_token_logic.opt.wasm";
+const FT_MAIN_WASM: &str =
+    "../target/wasm32-unknown-unknown/release/sharded_fungible_token.opt.wasm";
+
+pub fn actor_id(program: &Program<'_>) -> ActorId {
+    ActorId::new(program.id().into_bytes())
+}
+
+pub fn init_ft(sys: &System) -> Program<'_> {
+    let storage_code_id = sys.submit_code(FT_STORAGE_WASM);
+    let logic_code_id = sys.submit_code(FT_LOGIC_WASM);
+    let ft = Program::from_file(sys, FT_MAIN_WASM);
+    let res = ft.send(
+        ADMIN,
+        InitFToken {
+            storage_code_hash: storage_code_id.into_bytes().into(),
+            ft_logic_code_hash: logic_code_id.into_bytes().into(),
+        },
+    );
+    assert!(!res.main_failed());
+
+    ft
+}
+
+/// Initializes a store charging `ft_contract_id` tokens with a sword priced at
+/// [`PRICE`].
+pub fn init_store(sys: &System, ft_contract_id: ActorId) -> Program<'_> {
+    let store = Program::from_file(sys, STORE_WASM);
+    let res = store.send(ADMIN, ft_contract_id);
+    assert!(!res.main_failed());
+
+    let res = store.send(
+        ADMIN,
+        StoreAction::CreateAttribute {
+            attribute_id: SWORD_ID,
+            attr_metadata: AttrMetadata {
+                title: String::from("Sword"),
+                description: String::from("sword"),
+                media: String::from("sword"),
+            },
+            price: PRICE,
+        },
+    );
+    assert!(!res.main_failed());
+
+    store
+}
+
+/// Initializes a Tamagotchi owned by `owner` that pays with `ft_contract_id`
+/// tokens.
+pub fn init_tamagotchi(sys: &System, owner: u64, ft_contract_id: ActorId) -> Program<'_> {
+    let tamagotchi = Program::current(sys);
+    let res = tamagotchi.send(owner, String::from("Tama"));
+    assert!(!res.main_failed());
+
+    let res = tamagotchi.send(owner, TmgAction::SetFTokenContract(ft_contract_id));
+    let log = Log::builder()
+        .dest(owner)
+        .payload(TmgEvent::FTokenContractSet);
+    assert!(res.contains(&log));
+
+    tamagotchi
+}
+
+pub fn mint(ft: &Program<'_>, recipient: ActorId, amount: u128, transaction_id: u64) {
+    let res = ft.send(
+        ADMIN,
+        FTokenAction::Message {
+            transaction_id,
+            payload: LogicAction::Mint { recipient, amount },
+        },
+    );
+    assert!(!res.main_failed());
+}
+
+pub fn check_ft_balance(ft: &Program<'_>, account: ActorId, balance: u128) {
+    let res = ft.send(ADMIN, FTokenAction::GetBalance(account));
+    let log = Log::builder()
+        .dest(ADMIN)
+        .payload(FTokenEvent::Balance(balance));
+    assert!(res.contains(&log));
+}
+
+pub fn check_attributes(store: &Program<'_>, tamagotchi_id: ActorId, attributes: &[AttributeId]) {
+    let res = store.send(ADMIN, StoreAction::GetAttributes { tamagotchi_id });
+    let log = Log::builder().dest(ADMIN).payload(StoreEvent::Attributes {
+        attributes: attributes.iter().copied().collect(),
+    });
+    assert!(res.contains(&log));
+}
@@ -0,0 +1,113 @@
+mod common;
+
+use common::{
+    actor_id, check_attributes, init_ft, init_store, init_tamagotchi, mint, OWNER, PRICE, SWORD_ID,
+    USER,
+};
+use gstd::ActorId;
+use gtest::{Log, System};
+use tamagotchi_shop_io::{EquipmentSlot, TmgAction, TmgEvent};
+
+const OTHER_TAMAGOTCHI: u64 = 200;
+
+#[test]
+fn equip_unequip_and_transfer() {
+    let sys = System::new();
+    sys.init_logger();
+    let ft = init_ft(&sys);
+    let store = init_store(&sys, actor_id(&ft));
+    let tamagotchi = init_tamagotchi(&sys, OWNER, actor_id(&ft));
+    let store_id = actor_id(&store);
+    let tamagotchi_id = actor_id(&tamagotchi);
+    mint(&ft, tamagotchi_id, PRICE, 0);
+
+    let res = tamagotchi.send(
+        OWNER,
+        TmgAction::ApproveAndBuy {
+            store_id,
+            attribute_id: SWORD_ID,
+        },
+    );
+    assert!(!res.main_failed());
+
+    let equip = |attribute_id, slot| TmgAction::Equip {
+        store_id,
+        attribute_id,
+        slot,
+    };
+
+    // must fail since USER isn't the owner or an approved account
+    let res = tamagotchi.send(USER, equip(SWORD_ID, EquipmentSlot::Weapon));
+    assert!(res.main_failed());
+
+    let res = tamagotchi.send(OWNER, equip(2, EquipmentSlot::Weapon));
+    let log = Log::builder()
+        .dest(OWNER)
+        .payload(TmgEvent::AttributeNotOwned(2));
+    assert!(res.contains(&log));
+
+    let res = tamagotchi.send(OWNER, equip(SWORD_ID, EquipmentSlot::Weapon));
+    let log = Log::builder().dest(OWNER).payload(TmgEvent::Equipped {
+        slot: EquipmentSlot::Weapon,
+        attribute_id: SWORD_ID,
+    });
+    assert!(res.contains(&log));
+
+    // must fail since the sword is already in the weapon slot
+    let res = tamagotchi.send(OWNER, equip(SWORD_ID, EquipmentSlot::Shield));
+    assert!(res.main_failed());
+
+    let transfer = TmgAction::TransferAttribute {
+        store_id,
+        attribute_id: SWORD_ID,
+        to: OTHER_TAMAGOTCHI.into(),
+    };
+    let res = tamagotchi.send(OWNER, transfer);
+    let log = Log::builder()
+        .dest(OWNER)
+        .payload(TmgEvent::AttributeEquipped(SWORD_ID));
+    assert!(res.contains(&log));
+
+    let res = tamagotchi.send(
+        USER,
+        TmgAction::Unequip {
+            slot: EquipmentSlot::Weapon,
+        },
+    );
+    assert!(res.main_failed());
+
+    let res = tamagotchi.send(
+        OWNER,
+        TmgAction::Unequip {
+            slot: EquipmentSlot::Weapon,
+        },
+    );
+    let log = Log::builder()
+        .dest(OWNER)
+        .payload(TmgEvent::Unequipped(EquipmentSlot::Weapon));
+    assert!(res.contains(&log));
+
+    let transfer = || TmgAction::TransferAttribute {
+        store_id,
+        attribute_id: SWORD_ID,
+        to: OTHER_TAMAGOTCHI.into(),
+    };
+    let res = tamagotchi.send(USER, transfer());
+    assert!(res.main_failed());
+
+    // an approved account manages the attributes like the owner
+    let res = tamagotchi.send(OWNER, TmgAction::Approve(USER.into()));
+    assert!(!res.main_failed());
+
+    let res = tamagotchi.send(USER, transfer());
+    let log = Log::builder()
+        .dest(USER)
+        .payload(TmgEvent::AttributeTransferred {
+            attribute_id: SWORD_ID,
+            to: OTHER_TAMAGOTCHI.into(),
+        });
+    assert!(res.contains(&log));
+
+    check_attributes(&store, tamagotchi_id, &[]);
+    check_attributes(&store, ActorId::from(OTHER_TAMAGOTCHI), &[SWORD_ID]);
+}
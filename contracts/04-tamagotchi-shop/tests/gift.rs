@@ -0,0 +1,128 @@
+mod common;
+
+use common::{
+    actor_id, check_attributes, init_ft, init_store, init_tamagotchi, mint, ADMIN, OWNER, PRICE,
+    SWORD_ID, USER,
+};
+use gtest::{Log, System};
+use store_io::{AttributeId, StoreAction};
+use tamagotchi_shop_io::{TmgAction, TmgEvent};
+
+const SHIELD_ID: AttributeId = 2;
+
+#[test]
+fn recipient_is_notified_of_transfers_and_gifts() {
+    let sys = System::new();
+    sys.init_logger();
+    let ft = init_ft(&sys);
+    let store = init_store(&sys, actor_id(&ft));
+    let sender = init_tamagotchi(&sys, OWNER, actor_id(&ft));
+    let recipient = init_tamagotchi(&sys, USER, actor_id(&ft));
+    let store_id = actor_id(&store);
+    let sender_id = actor_id(&sender);
+    let recipient_id = actor_id(&recipient);
+    mint(&ft, sender_id, PRICE, 0);
+
+    let res = sender.send(
+        OWNER,
+        TmgAction::ApproveAndBuy {
+            store_id,
+            attribute_id: SWORD_ID,
+        },
+    );
+    assert!(!res.main_failed());
+
+    // the recipient Tamagotchi handles the store notification and passes
+    // it on to its owner
+    let res = sender.send(
+        OWNER,
+        TmgAction::TransferAttribute {
+            store_id,
+            attribute_id: SWORD_ID,
+            to: recipient_id,
+        },
+    );
+    let log = Log::builder()
+        .dest(OWNER)
+        .payload(TmgEvent::AttributeTransferred {
+            attribute_id: SWORD_ID,
+            to: recipient_id,
+        });
+    assert!(res.contains(&log));
+    let log =
+        Log::builder()
+            .source(recipient.id())
+            .dest(USER)
+            .payload(TmgEvent::AttributeReceived {
+                store_id,
+                attribute_id: SWORD_ID,
+                from: sender_id,
+            });
+    assert!(res.contains(&log));
+    check_attributes(&store, recipient_id, &[SWORD_ID]);
+
+    let res = store.send(
+        ADMIN,
+        StoreAction::SetNativePrice {
+            attribute_id: SWORD_ID,
+            price: Some(PRICE),
+        },
+    );
+    assert!(!res.main_failed());
+
+    sys.mint_to(OWNER, PRICE);
+    let res = sender.send_with_value(
+        OWNER,
+        TmgAction::GiftAttribute {
+            store_id,
+            attribute_id: SWORD_ID,
+            to: recipient_id,
+        },
+        PRICE,
+    );
+    let log = Log::builder()
+        .dest(OWNER)
+        .payload(TmgEvent::AttributeGifted {
+            attribute_id: SWORD_ID,
+            to: recipient_id,
+        });
+    assert!(res.contains(&log));
+    let log =
+        Log::builder()
+            .source(recipient.id())
+            .dest(USER)
+            .payload(TmgEvent::AttributeReceived {
+                store_id,
+                attribute_id: SWORD_ID,
+                from: sender_id,
+            });
+    assert!(res.contains(&log));
+
+    // must fail since only the owner or an approved account spends the
+    // sender's tokens on gifts
+    let res = sender.send(
+        USER,
+        TmgAction::GiftAttribute {
+            store_id,
+            attribute_id: SWORD_ID,
+            to: recipient_id,
+        },
+    );
+    assert!(res.main_failed());
+
+    // a gift of an attribute that can't be bought fails and isn't notified
+    let res = sender.send(
+        OWNER,
+        TmgAction::GiftAttribute {
+            store_id,
+            attribute_id: SHIELD_ID,
+            to: recipient_id,
+        },
+    );
+    let log = Log::builder()
+        .dest(OWNER)
+        .payload(TmgEvent::ErrorDuringPurchase);
+    assert!(res.contains(&log));
+    let log = Log::builder().source(recipient.id()).dest(USER);
+    assert!(!res.contains(&log));
+}
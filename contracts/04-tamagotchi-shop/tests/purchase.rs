@@ -0,0 +1,190 @@
+mod common;
+
+use common::{
+    actor_id, check_attributes, check_ft_balance, init_ft, init_store, init_tamagotchi, mint,
+    ADMIN, OWNER, PRICE, SWORD_ID, USER,
+};
+use gtest::{Log, System};
+use store_io::{BundleId, StoreAction};
+use tamagotchi_shop_io::{TmgAction, TmgEvent};
+
+const BUNDLE_ID: BundleId = 1;
+
+#[test]
+fn approve_and_buy() {
+    let sys = System::new();
+    sys.init_logger();
+    let ft = init_ft(&sys);
+    let store = init_store(&sys, actor_id(&ft));
+    let tamagotchi = init_tamagotchi(&sys, OWNER, actor_id(&ft));
+    let store_id = actor_id(&store);
+    let tamagotchi_id = actor_id(&tamagotchi);
+    mint(&ft, tamagotchi_id, PRICE, 0);
+
+    let res = tamagotchi.send(
+        OWNER,
+        TmgAction::ApproveAndBuy {
+            store_id,
+            attribute_id: SWORD_ID,
+        },
+    );
+    let log = Log::builder()
+        .dest(OWNER)
+        .payload(TmgEvent::AttributeBought(SWORD_ID));
+    assert!(res.contains(&log));
+
+    check_attributes(&store, tamagotchi_id, &[SWORD_ID]);
+    check_ft_balance(&ft, tamagotchi_id, 0);
+    check_ft_balance(&ft, store_id, PRICE);
+
+    // the approval was cleared, so an approval of another amount works
+    let res = tamagotchi.send(
+        OWNER,
+        TmgAction::ApproveTokens {
+            account: store_id,
+            amount: PRICE * 2,
+        },
+    );
+    let log = Log::builder()
+        .dest(OWNER)
+        .payload(TmgEvent::TokensApproved {
+            account: store_id,
+            amount: PRICE * 2,
+        });
+    assert!(res.contains(&log));
+}
+
+#[test]
+fn approve_and_buy_retry_charges_once() {
+    // wherever the gas runs out, retrying the purchase completes it and the
+    // Tamagotchi pays the price only once
+    for gas_limit in (1..=15).map(|i| i * 1_000_000_000) {
+        let sys = System::new();
+        sys.init_logger();
+        let ft = init_ft(&sys);
+        let store = init_store(&sys, actor_id(&ft));
+        let tamagotchi = init_tamagotchi(&sys, OWNER, actor_id(&ft));
+        let store_id = actor_id(&store);
+        let tamagotchi_id = actor_id(&tamagotchi);
+        mint(&ft, tamagotchi_id, PRICE * 2, 0);
+
+        let action = TmgAction::ApproveAndBuy {
+            store_id,
+            attribute_id: SWORD_ID,
+        };
+        tamagotchi.send_with_gas(OWNER, action, gas_limit, 0);
+
+        let res = tamagotchi.send(
+            OWNER,
+            TmgAction::ApproveAndBuy {
+                store_id,
+                attribute_id: SWORD_ID,
+            },
+        );
+        let log = Log::builder()
+            .dest(OWNER)
+            .payload(TmgEvent::AttributeBought(SWORD_ID));
+        assert!(res.contains(&log));
+
+        check_attributes(&store, tamagotchi_id, &[SWORD_ID]);
+        check_ft_balance(&ft, tamagotchi_id, PRICE);
+        check_ft_balance(&ft, store_id, PRICE);
+    }
+}
+
+#[test]
+fn rejected_purchase_is_dropped() {
+    let sys = System::new();
+    sys.init_logger();
+    let ft = init_ft(&sys);
+    let store = init_store(&sys, actor_id(&ft));
+    let tamagotchi = init_tamagotchi(&sys, OWNER, actor_id(&ft));
+    let store_id = actor_id(&store);
+    let tamagotchi_id = actor_id(&tamagotchi);
+    mint(&ft, tamagotchi_id, PRICE, 0);
+
+    let action = || TmgAction::ApproveAndBuy {
+        store_id,
+        attribute_id: SWORD_ID,
+    };
+
+    // must fail since only the owner or an approved account buys
+    let res = tamagotchi.send(USER, action());
+    assert!(res.main_failed());
+
+    let res = store.send(ADMIN, StoreAction::Pause);
+    assert!(!res.main_failed());
+    let res = tamagotchi.send(OWNER, action());
+    let log = Log::builder()
+        .dest(OWNER)
+        .payload(TmgEvent::ErrorDuringPurchase);
+    assert!(res.contains(&log));
+
+    // the rejected purchase doesn't block the next one
+    let res = store.send(ADMIN, StoreAction::Unpause);
+    assert!(!res.main_failed());
+    let res = tamagotchi.send(OWNER, action());
+    let log = Log::builder()
+        .dest(OWNER)
+        .payload(TmgEvent::AttributeBought(SWORD_ID));
+    assert!(res.contains(&log));
+
+    check_attributes(&store, tamagotchi_id, &[SWORD_ID]);
+    check_ft_balance(&ft, tamagotchi_id, 0);
+}
+
+#[test]
+fn approved_account_buys_bundle() {
+    let sys = System::new();
+    sys.init_logger();
+    let ft = init_ft(&sys);
+    let store = init_store(&sys, actor_id(&ft));
+    let tamagotchi = init_tamagotchi(&sys, OWNER, actor_id(&ft));
+    let store_id = actor_id(&store);
+    let tamagotchi_id = actor_id(&tamagotchi);
+    mint(&ft, tamagotchi_id, PRICE, 0);
+
+    let res = store.send(
+        ADMIN,
+        StoreAction::CreateBundle {
+            bundle_id: BUNDLE_ID,
+            attributes: [SWORD_ID].into(),
+            price: PRICE,
+            supply: None,
+        },
+    );
+    assert!(!res.main_failed());
+    let res = tamagotchi.send(
+        OWNER,
+        TmgAction::ApproveTokens {
+            account: store_id,
+            amount: PRICE,
+        },
+    );
+    assert!(!res.main_failed());
+
+    let buy_bundle = TmgAction::BuyBundle {
+        store_id,
+        bundle_id: BUNDLE_ID,
+    };
+    // must fail since only the owner or an approved account buys
+    let res = tamagotchi.send(USER, buy_bundle);
+    assert!(res.main_failed());
+
+    let res = tamagotchi.send(OWNER, TmgAction::Approve(USER.into()));
+    assert!(!res.main_failed());
+    let res = tamagotchi.send(
+        USER,
+        TmgAction::BuyBundle {
+            store_id,
+            bundle_id: BUNDLE_ID,
+        },
+    );
+    let log = Log::builder()
+        .dest(USER)
+        .payload(TmgEvent::BundleBought(BUNDLE_ID));
+    assert!(res.contains(&log));
+
+    check_attributes(&store, tamagotchi_id, &[SWORD_ID]);
+    check_ft_balance(&ft, tamagotchi_id, 0);
+}
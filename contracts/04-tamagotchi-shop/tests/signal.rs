@@ -0,0 +1,58 @@
+mod common;
+
+use common::{actor_id, init_ft, init_tamagotchi, OWNER, USER};
+use gtest::{Log, Program, System};
+use tamagotchi_shop_io::{TmgAction, TmgEvent};
+
+const AMOUNT: u128 = 1_000;
+
+fn approve_tokens(tamagotchi: &Program<'_>, amount: u128) -> bool {
+    let res = tamagotchi.send(
+        OWNER,
+        TmgAction::ApproveTokens {
+            account: USER.into(),
+            amount,
+        },
+    );
+    let log = Log::builder()
+        .dest(OWNER)
+        .payload(TmgEvent::TokensApproved {
+            account: USER.into(),
+            amount,
+        });
+    res.contains(&log)
+}
+
+#[test]
+fn approval_out_of_gas_is_rolled_back() {
+    let sys = System::new();
+    sys.init_logger();
+    let ft = init_ft(&sys);
+    let tamagotchi = init_tamagotchi(&sys, OWNER, actor_id(&ft));
+
+    // whether the gas runs out before sending the approval or while waiting
+    // for the reply, the next approval of another amount isn't blocked by a
+    // pending one
+    let mut failed = 0;
+    let mut approved = 0;
+    for gas_limit in (1..=20).map(|i| i * 1_000_000_000) {
+        let res = tamagotchi.send_with_gas(
+            OWNER,
+            TmgAction::ApproveTokens {
+                account: USER.into(),
+                amount: AMOUNT,
+            },
+            gas_limit,
+            0,
+        );
+        if res.main_failed() {
+            failed += 1;
+        } else {
+            approved += 1;
+        }
+
+        assert!(approve_tokens(&tamagotchi, AMOUNT * 2));
+    }
+    assert!(failed > 0, "The gas never ran out");
+    assert!(approved > 0, "The approval never succeeded");
+}
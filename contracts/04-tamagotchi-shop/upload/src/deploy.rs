@@ -0,0 +1,274 @@
+use crate::{manifest::Manifest, payload::invalid};
+use clap::ValueEnum;
+use gclient::{
+    errors::{Gear, ModuleError},
+    Error, GearApi, Result,
+};
+use gear_core::ids::{CodeId, ProgramId};
+use gsdk::gp::Encode;
+use gstd::ActorId;
+use sharded_fungible_token_io::InitFToken;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+pub const FT_STORAGE_WASM: &str = "sharded_fungible_token_storage";
+pub const FT_LOGIC_WASM: &str = "sharded_fungible_token_logic";
+pub const FT_MAIN_WASM: &str = "sharded_fungible_token";
+pub const STORE_WASM: &str = "store";
+pub const TAMAGOTCHI_WASM: &str = "tamagotchi_shop";
+pub const AUTO_TAMAGOTCHI_WASM: &str = "tamagotchi_auto";
+pub const ARMY_WASM: &str = "tamagotchi_army";
+pub const BATTLE_STORE_WASM: &str = "tamagotchi_store";
+pub const BATTLE_WASM: &str = "tamagotchi_battle";
+pub const ESCROW_WASM: &str = "escrow";
+pub const ESCROW_FACTORY_WASM: &str = "escrow_factory";
+
+/// Every program of the stack in an order that satisfies their init payloads.
+pub const PROGRAMS: [&str; 8] = [
+    FT_MAIN_WASM,
+    STORE_WASM,
+    TAMAGOTCHI_WASM,
+    ARMY_WASM,
+    BATTLE_STORE_WASM,
+    BATTLE_WASM,
+    ESCROW_WASM,
+    ESCROW_FACTORY_WASM,
+];
+
+const TAMAGOTCHI_NAME: &str = "Tamagotchi";
+const COST_TO_UPGRADE_WEAPONS: u128 = 100;
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum Profile {
+    Debug,
+    Release,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum Target {
+    Ft,
+    Store,
+    Tamagotchi,
+    Army,
+    Battle,
+    Escrow,
+    EscrowFactory,
+    All,
+}
+
+/// Terms of the escrow program, the deploying account is the seller.
+pub struct EscrowTerms {
+    pub buyer: Option<ActorId>,
+    pub price: u128,
+}
+
+/// Uploads codes and creates programs, recording every id in the manifest.
+pub struct Deployer {
+    api: GearApi,
+    escrow_terms: EscrowTerms,
+    wasm_dir: PathBuf,
+    manifest: Manifest,
+    manifest_path: PathBuf,
+}
+
+impl Deployer {
+    pub fn new(
+        api: GearApi,
+        profile: Profile,
+        manifest_path: &Path,
+        escrow_terms: EscrowTerms,
+    ) -> Result<Self> {
+        let profile = match profile {
+            Profile::Debug => "debug",
+            Profile::Release => "release",
+        };
+        Ok(Self {
+            api,
+            escrow_terms,
+            wasm_dir: Path::new("target/wasm32-unknown-unknown").join(profile),
+            manifest: Manifest::load(manifest_path)?,
+            manifest_path: manifest_path.into(),
+        })
+    }
+
+    pub fn api(&self) -> &GearApi {
+        &self.api
+    }
+
+    pub fn account(&self) -> ActorId {
+        let account_id: [u8; 32] = self.api.account_id().clone().into();
+        account_id.into()
+    }
+
+    pub async fn deploy(&mut self, target: Target) -> Result<()> {
+        let file_names: &[&str] = match target {
+            Target::Ft => &[FT_MAIN_WASM],
+            Target::Store => &[STORE_WASM],
+            Target::Tamagotchi => &[TAMAGOTCHI_WASM],
+            Target::Army => &[ARMY_WASM],
+            Target::Battle => &[BATTLE_WASM],
+            Target::Escrow => &[ESCROW_WASM],
+            Target::EscrowFactory => &[ESCROW_FACTORY_WASM],
+            Target::All => &PROGRAMS,
+        };
+        for file_name in file_names {
+            self.deployed(file_name).await?;
+        }
+        Ok(())
+    }
+
+    /// Returns the program of `file_name`, creating it with the programs it
+    /// depends on if the manifest doesn't have it yet.
+    pub async fn deployed(&mut self, file_name: &str) -> Result<ProgramId> {
+        match file_name {
+            FT_MAIN_WASM => self.ft().await,
+            BATTLE_STORE_WASM => self.battle_store().await,
+            _ => {
+                if let Some(program_id) = self.manifest.program(file_name) {
+                    return Ok(program_id);
+                }
+                let payload = self.init_payload(file_name).await?;
+                self.program(file_name, payload).await
+            }
+        }
+    }
+
+    /// Encodes the init payload of `file_name`, deploying what it refers to.
+    pub async fn init_payload(&mut self, file_name: &str) -> Result<Vec<u8>> {
+        let payload = match file_name {
+            FT_MAIN_WASM => self.ft_payload().await?,
+            STORE_WASM => to_actor_id(self.ft().await?).encode(),
+            TAMAGOTCHI_WASM => TAMAGOTCHI_NAME.encode(),
+            ARMY_WASM => self.code(AUTO_TAMAGOTCHI_WASM).await?.encode(),
+            BATTLE_STORE_WASM => self.battle_store_payload().await?,
+            BATTLE_WASM => tamagotchi_battle_io::BattleInit {
+                tmg_store_id: to_actor_id(self.battle_store().await?),
+            }
+            .encode(),
+            ESCROW_WASM => escrow_io::InitEscrow {
+                seller: self.account(),
+                buyer: self.escrow_terms.buyer.unwrap_or_else(|| self.account()),
+                price: self.escrow_terms.price,
+            }
+            .encode(),
+            ESCROW_FACTORY_WASM => self.code(ESCROW_WASM).await?.encode(),
+            _ => return Err(invalid(format!("`{file_name}` isn't a known program"))),
+        };
+        Ok(payload)
+    }
+
+    pub async fn ft(&mut self) -> Result<ProgramId> {
+        if let Some(program_id) = self.manifest.program(FT_MAIN_WASM) {
+            return Ok(program_id);
+        }
+
+        let payload = self.ft_payload().await?;
+        self.program(FT_MAIN_WASM, payload).await
+    }
+
+    async fn ft_payload(&mut self) -> Result<Vec<u8>> {
+        let ft_storage_code_id = self.code(FT_STORAGE_WASM).await?;
+        let ft_logic_code_id = self.code(FT_LOGIC_WASM).await?;
+        let payload = InitFToken {
+            storage_code_hash: ft_storage_code_id.into_bytes().into(),
+            ft_logic_code_hash: ft_logic_code_id.into_bytes().into(),
+        };
+        Ok(payload.encode())
+    }
+
+    async fn battle_store(&mut self) -> Result<ProgramId> {
+        if let Some(program_id) = self.manifest.program(BATTLE_STORE_WASM) {
+            return Ok(program_id);
+        }
+
+        let payload = self.battle_store_payload().await?;
+        self.program(BATTLE_STORE_WASM, payload).await
+    }
+
+    async fn battle_store_payload(&mut self) -> Result<Vec<u8>> {
+        let payload = tamagotchi_store_io::StoreInit {
+            ft_contract_id: to_actor_id(self.ft().await?),
+            cost_to_upgrade_weapons: COST_TO_UPGRADE_WEAPONS,
+        };
+        Ok(payload.encode())
+    }
+
+    /// Uploads the code of `file_name` unless the manifest already has it.
+    pub async fn code(&mut self, file_name: &str) -> Result<CodeId> {
+        if let Some(code_id) = self.manifest.code(file_name) {
+            return Ok(code_id);
+        }
+
+        println!("Uploading '{file_name}' code");
+        let code = fs::read(self.wasm_dir.join(format!("{file_name}.opt.wasm")))?;
+        let code_id = upload_code(&self.api, &code).await?;
+        println!("    Code ID: {code_id}\n");
+
+        self.manifest.set_code(file_name, code_id);
+        self.manifest.save(&self.manifest_path)?;
+        Ok(code_id)
+    }
+
+    async fn program(&mut self, file_name: &str, payload: Vec<u8>) -> Result<ProgramId> {
+        let code_id = self.code(file_name).await?;
+
+        let gas_info = self
+            .api
+            .calculate_create_gas(None, code_id, payload.clone(), 0, false)
+            .await?;
+        println!("Calculated gas: {}", gas_info.min_limit);
+
+        let mut listener = self.api.subscribe().await?;
+        let (message_id, program_id, _) = self
+            .api
+            .create_program(code_id, salt(), payload, 2 * gas_info.min_limit, 0)
+            .await?;
+        // a program whose init failed isn't recorded, so the next run
+        // creates it again instead of building on it
+        if !listener.message_processed(message_id).await?.succeed() {
+            return Err(invalid(format!(
+                "'{file_name}' program {program_id} failed to initialize"
+            )));
+        }
+        println!("'{file_name}' program created, ID: {program_id}\n");
+
+        self.manifest.set_program(file_name, program_id);
+        self.manifest.save(&self.manifest_path)?;
+        Ok(program_id)
+    }
+}
+
+pub async fn upload_code(api: &GearApi, code: &[u8]) -> Result<CodeId> {
+    let res = api.upload_code(code).await;
+    let generated_code_id = CodeId::generate(code);
+    match res {
+        Err(Error::Module(ModuleError::Gear(Gear::CodeAlreadyExists))) => {
+            println!("    Code already exists, skipping upload");
+            Ok(generated_code_id)
+        }
+        Ok((code_id, _)) => {
+            assert_eq!(code_id, generated_code_id);
+            println!("    Code uploaded");
+            Ok(code_id)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+pub fn to_actor_id(program_id: ProgramId) -> ActorId {
+    program_id.into_bytes().into()
+}
+
+/// The same code can be instantiated several times, so the salt has to change
+/// between deployments.
+pub fn salt() -> Vec<u8> {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("System time is before the UNIX epoch")
+        .as_nanos()
+        .to_le_bytes()
+        .to_vec()
+}
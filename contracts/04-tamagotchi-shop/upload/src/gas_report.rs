@@ -0,0 +1,211 @@
+use crate::deploy::{
+    to_actor_id, Deployer, ARMY_WASM, AUTO_TAMAGOTCHI_WASM, BATTLE_WASM, ESCROW_FACTORY_WASM,
+    ESCROW_WASM, PROGRAMS, STORE_WASM, TAMAGOTCHI_WASM,
+};
+use gclient::Result;
+use gsdk::gp::Encode;
+
+const TAMAGOTCHI_NAME: &str = "Report";
+
+/// The hardcoded gas constant an action has to fit in.
+type Limit = (&'static str, u64);
+/// Program, action name, payload and limit of a calculated action.
+type Handle = (&'static str, &'static str, Vec<u8>, Option<Limit>);
+
+struct Row {
+    program: &'static str,
+    action: &'static str,
+    gas: std::result::Result<u64, String>,
+    limit: Option<Limit>,
+}
+
+/// Calculates the gas of every init and of a representative set of actions
+/// and prints them as a table.
+///
+/// Actions are calculated against the programs in the manifest, missing
+/// ones are deployed first, so prefer running it against a dev node.
+pub async fn gas_report(deployer: &mut Deployer) -> Result<()> {
+    let mut rows = vec![];
+
+    for file_name in PROGRAMS {
+        let code_id = deployer.code(file_name).await?;
+        let payload = deployer.init_payload(file_name).await?;
+        let limit = (file_name == ESCROW_WASM).then_some((
+            "escrow-factory GAS_FOR_CREATION",
+            escrow_factory_io::GAS_FOR_CREATION,
+        ));
+        let gas = create_gas(deployer, code_id, payload).await;
+        rows.push(Row {
+            program: file_name,
+            action: "init",
+            gas,
+            limit,
+        });
+    }
+
+    // the army creates Tamagotchis from the code, it isn't a program itself
+    let code_id = deployer.code(AUTO_TAMAGOTCHI_WASM).await?;
+    let payload = tamagotchi_auto_io::TmgInit {
+        owner: deployer.account(),
+        name: TAMAGOTCHI_NAME.into(),
+        factory: None,
+        imported: None,
+    };
+    rows.push(Row {
+        program: AUTO_TAMAGOTCHI_WASM,
+        action: "init",
+        gas: create_gas(deployer, code_id, payload.encode()).await,
+        limit: Some((
+            "tamagotchi-army GAS_FOR_CREATION",
+            tamagotchi_army_io::GAS_FOR_CREATION,
+        )),
+    });
+
+    let account = deployer.account();
+    let store_id = deployer.deployed(STORE_WASM).await?;
+    let tamagotchi_id = deployer.deployed(TAMAGOTCHI_WASM).await?;
+    let handles: [Handle; 10] = [
+        (
+            TAMAGOTCHI_WASM,
+            "Feed",
+            tamagotchi_shop_io::TmgAction::Feed.encode(),
+            None,
+        ),
+        (
+            TAMAGOTCHI_WASM,
+            "BuyAttribute",
+            tamagotchi_shop_io::TmgAction::BuyAttribute {
+                store_id: to_actor_id(store_id),
+                attribute_id: 0,
+            }
+            .encode(),
+            None,
+        ),
+        (
+            STORE_WASM,
+            "BuyAttribute",
+            store_io::StoreAction::BuyAttribute { attribute_id: 0 }.encode(),
+            None,
+        ),
+        (
+            ARMY_WASM,
+            "CreateTamagotchi",
+            tamagotchi_army_io::TamagotchiFactoryAction::CreateTamagotchi {
+                name: TAMAGOTCHI_NAME.into(),
+            }
+            .encode(),
+            None,
+        ),
+        (
+            BATTLE_WASM,
+            "Register",
+            tamagotchi_battle_io::BattleAction::Register {
+                tamagotchi_id: to_actor_id(tamagotchi_id),
+                attributes: Default::default(),
+            }
+            .encode(),
+            None,
+        ),
+        (
+            BATTLE_WASM,
+            "Move",
+            tamagotchi_battle_io::BattleAction::Move(Default::default()).encode(),
+            None,
+        ),
+        (
+            BATTLE_WASM,
+            "UpdateInfo",
+            tamagotchi_battle_io::BattleAction::UpdateInfo.encode(),
+            Some((
+                "tamagotchi-battle GAS_AMOUNT",
+                tamagotchi_battle_io::GAS_AMOUNT,
+            )),
+        ),
+        (
+            ESCROW_WASM,
+            "Deposit",
+            escrow_io::EscrowAction::Deposit(account).encode(),
+            None,
+        ),
+        (
+            ESCROW_FACTORY_WASM,
+            "CreateEscrow",
+            escrow_factory_io::FactoryAction::CreateEscrow {
+                seller: account,
+                buyer: account,
+                price: 0,
+            }
+            .encode(),
+            None,
+        ),
+        (
+            ESCROW_FACTORY_WASM,
+            "Deposit",
+            escrow_factory_io::FactoryAction::Deposit(0).encode(),
+            None,
+        ),
+    ];
+    for (program, action, payload, limit) in handles {
+        let program_id = deployer.deployed(program).await?;
+        let gas = deployer
+            .api()
+            .calculate_handle_gas(None, program_id, payload, 0, true)
+            .await
+            .map(|gas_info| gas_info.min_limit)
+            .map_err(|e| e.to_string());
+        rows.push(Row {
+            program,
+            action,
+            gas,
+            limit,
+        });
+    }
+
+    print_table(&rows);
+    Ok(())
+}
+
+async fn create_gas(
+    deployer: &Deployer,
+    code_id: gear_core::ids::CodeId,
+    payload: Vec<u8>,
+) -> std::result::Result<u64, String> {
+    deployer
+        .api()
+        .calculate_create_gas(None, code_id, payload, 0, true)
+        .await
+        .map(|gas_info| gas_info.min_limit)
+        .map_err(|e| e.to_string())
+}
+
+fn print_table(rows: &[Row]) {
+    println!("{:<32} {:<18} {:>16}  Limit", "Program", "Action", "Gas");
+    let mut exceeded = 0;
+    for row in rows {
+        let gas = match &row.gas {
+            Ok(gas) => gas.to_string(),
+            Err(_) => "failed".into(),
+        };
+        let limit = match (row.limit, &row.gas) {
+            (Some((name, limit)), Ok(gas)) if *gas > limit => {
+                exceeded += 1;
+                format!("EXCEEDS {name} = {limit}")
+            }
+            (Some((name, limit)), _) => format!("{name} = {limit}"),
+            (None, _) => String::new(),
+        };
+        println!(
+            "{:<32} {:<18} {:>16}  {limit}",
+            row.program, row.action, gas
+        );
+    }
+
+    for row in rows {
+        if let Err(e) = &row.gas {
+            println!("\n{} {}: {e}", row.program, row.action);
+        }
+    }
+    if exceeded > 0 {
+        println!("\n{exceeded} action(s) don't fit in their gas constants");
+    }
+}
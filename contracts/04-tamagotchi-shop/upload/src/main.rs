@@ -1,90 +1,143 @@
-use gclient::{
-    errors::{Gear, ModuleError},
-    Error, GearApi, Result,
-};
-use gear_core::ids::CodeId;
-use gsdk::gp::Encode;
-use sharded_fungible_token_io::InitFToken;
-use std::fs;
-
-const FT_STORAGE_WASM: &str = "sharded_fungible_token_storage";
-const FT_LOGIC_WASM: &str = "sharded_fungible_token_logic";
-const FT_MAIN_WASM: &str = "sharded_fungible_token";
+mod deploy;
+mod gas_report;
+mod manifest;
+mod payload;
+mod scenario;
+mod seed;
 
-#[tokio::main(flavor = "current_thread")]
-async fn main() -> Result<()> {
-    let api = GearApi::init_with(
-        gclient::WSAddress::new("wss://testnet.vara.rs", Some(443)),
-        "//Alice",
-    )
-    .await?;
-    // To run local node use:
-    // let api = GearApi::dev_from_path("gear").await?;
-    println!("Connected to the node\n");
+use clap::{Parser, Subcommand};
+use deploy::{Deployer, EscrowTerms, Profile, Target};
+use gas_report::gas_report;
+use gclient::{GearApi, Result, WSAddress};
+use manifest::Manifest;
+use scenario::Scenario;
+use seed::{Catalog, StoreKind};
+use std::path::PathBuf;
 
-    let mut listener = api.subscribe().await?;
+const DEFAULT_NODE: &str = "wss://testnet.vara.rs";
 
-    assert!(listener.blocks_running().await?);
-    println!("Network is live\n");
+/// Deploys the Tamagotchi stack to a Gear node.
+#[derive(Parser)]
+struct Cli {
+    /// Node address, the testnet by default or the `wsAddress` of a scenario.
+    #[arg(long, global = true)]
+    node: Option<String>,
 
-    println!("Uploading 'FT Storage' code");
-    let ft_storage_code_id = upload_code(&api, FT_STORAGE_WASM).await?;
+    #[arg(long, global = true)]
+    port: Option<u16>,
 
-    println!("Uploading 'FT Logic' code");
-    let ft_logic_code_id = upload_code(&api, FT_LOGIC_WASM).await?;
+    /// Starts a local dev node from the given `gear` binary instead of
+    /// connecting to `--node`.
+    #[arg(long, global = true)]
+    dev: Option<PathBuf>,
 
-    println!("Uploading 'FT Main' code");
-    let ft_main_code_id = upload_code(&api, FT_MAIN_WASM).await?;
+    /// Secret URI of the deploying account.
+    #[arg(long, global = true, default_value = "//Alice")]
+    suri: String,
 
-    let payload = InitFToken {
-        storage_code_hash: ft_storage_code_id.into_bytes().into(),
-        ft_logic_code_hash: ft_logic_code_id.into_bytes().into(),
-    };
+    /// Build profile the wasm files are taken from.
+    #[arg(long, global = true, value_enum, default_value_t = Profile::Release)]
+    profile: Profile,
 
-    let user_id_bytes: [u8; 32] = api.account_id().clone().into();
-    let user_id = user_id_bytes.into();
-
-    let gas_info = api
-        .calculate_create_gas(Some(user_id), ft_main_code_id, vec![], 0, false)
-        .await?;
-    println!("Calculated gas: {}", gas_info.min_limit);
-
-    let (_, ft_main_prog_id, _) = api
-        .create_program(
-            ft_main_code_id,
-            b"salt",
-            payload.encode(),
-            2 * gas_info.min_limit,
-            0,
-        )
-        .await?;
-    println!("'FT Main' program created, ID: {ft_main_prog_id}\n");
+    /// JSON file with the code and program ids of the deployment.
+    #[arg(long, global = true, default_value = "deployment.json")]
+    manifest: PathBuf,
 
-    Ok(())
+    /// Buyer of the escrow, the deploying account by default.
+    #[arg(long, global = true, value_parser = parse_actor_id)]
+    escrow_buyer: Option<gstd::ActorId>,
+
+    #[arg(long, global = true, default_value_t = 1000)]
+    escrow_price: u128,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Uploads a program together with the programs it is initialized with.
+    Deploy {
+        #[arg(value_enum)]
+        target: Target,
+    },
+    /// Prints the gas of program inits and of their main actions.
+    GasReport,
+    /// Runs a scenario in the format of `upload/transactions.yml`, resuming
+    /// after the last completed step.
+    Run { scenario: PathBuf },
+    /// Creates the catalog attributes missing in a deployed store and mints
+    /// tokens to test accounts.
+    Seed {
+        /// TOML or JSON catalog.
+        catalog: PathBuf,
+
+        #[arg(long, value_enum, default_value_t = StoreKind::Shop)]
+        store: StoreKind,
+    },
 }
 
-async fn upload_code(api: &GearApi, file_name: &'static str) -> Result<CodeId> {
-    let code = fs::read(format!(
-        "target/wasm32-unknown-unknown/debug/{file_name}.opt.wasm"
-    ))?;
-    let res = api.upload_code(&code).await;
-    let ft_storage_code_id = CodeId::generate(&code);
-    let code_id = match res {
-        Err(Error::Module(ModuleError::Gear(Gear::CodeAlreadyExists))) => {
-            println!("    Code already exists, skipping upload");
-            ft_storage_code_id
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> Result<()> {
+    let cli = Cli::parse();
+
+    match &cli.command {
+        Command::Deploy { target } => {
+            let mut deployer = deployer(&cli).await?;
+            deployer.deploy(*target).await?;
+            println!("Manifest written to {}", cli.manifest.display());
         }
-        Ok((code_id, _)) => {
-            assert_eq!(code_id, ft_storage_code_id);
-            println!("    Code uploaded");
-            code_id
+        Command::GasReport => {
+            let mut deployer = deployer(&cli).await?;
+            gas_report(&mut deployer).await?;
         }
-        Err(e) => {
-            return Err(e);
+        Command::Run { scenario: path } => {
+            let scenario = Scenario::load(path)?;
+            let api = connect(&cli, &scenario.ws_address).await?;
+            scenario.run(&api, path).await?;
+        }
+        Command::Seed { catalog, store } => {
+            let catalog = Catalog::load(catalog)?;
+            let mut manifest = Manifest::load(&cli.manifest)?;
+            let api = connect(&cli, DEFAULT_NODE).await?;
+            catalog
+                .seed(&api, &mut manifest, &cli.manifest, *store)
+                .await?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn deployer(cli: &Cli) -> Result<Deployer> {
+    let api = connect(cli, DEFAULT_NODE).await?;
+    let escrow_terms = EscrowTerms {
+        buyer: cli.escrow_buyer,
+        price: cli.escrow_price,
+    };
+    Deployer::new(api, cli.profile, &cli.manifest, escrow_terms)
+}
+
+async fn connect(cli: &Cli, default_node: &str) -> Result<GearApi> {
+    let api = match &cli.dev {
+        Some(gear_path) => GearApi::dev_from_path(gear_path).await?.with(&cli.suri)?,
+        None => {
+            let node = cli.node.as_deref().unwrap_or(default_node);
+            GearApi::init_with(WSAddress::new(node, cli.port), &cli.suri).await?
         }
     };
+    println!("Connected to the node\n");
+
+    let mut listener = api.subscribe().await?;
 
-    println!("    Code ID: {code_id}\n");
+    assert!(listener.blocks_running().await?);
+    println!("Network is live\n");
+
+    Ok(api)
+}
 
-    Ok(code_id)
+fn parse_actor_id(id: &str) -> std::result::Result<gstd::ActorId, String> {
+    manifest::from_hex(id)
+        .map(Into::into)
+        .ok_or_else(|| format!("`{id}` isn't a hex encoded 32 byte id"))
 }
@@ -0,0 +1,75 @@
+use gclient::Result;
+use gear_core::ids::{CodeId, ProgramId};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fs, io, path::Path};
+
+/// Code and program ids of a deployment, keyed by the wasm file name.
+///
+/// Programs found in the manifest are reused, so deploying a program that
+/// depends on another one only creates what is missing. Remove an entry to
+/// deploy the program again.
+#[derive(Default, Serialize, Deserialize)]
+pub struct Manifest {
+    pub codes: BTreeMap<String, String>,
+    pub programs: BTreeMap<String, String>,
+    /// Fungible token transaction ids of the seeded mints.
+    #[serde(default)]
+    pub mints: BTreeMap<String, u64>,
+}
+
+impl Manifest {
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let manifest = serde_json::from_str(&fs::read_to_string(path)?).map_err(io::Error::from)?;
+        Ok(manifest)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let manifest = serde_json::to_string_pretty(self).map_err(io::Error::from)?;
+        fs::write(path, manifest)?;
+        Ok(())
+    }
+
+    pub fn code(&self, name: &str) -> Option<CodeId> {
+        self.codes
+            .get(name)
+            .and_then(|id| from_hex(id))
+            .map(Into::into)
+    }
+
+    pub fn program(&self, name: &str) -> Option<ProgramId> {
+        self.programs
+            .get(name)
+            .and_then(|id| from_hex(id))
+            .map(Into::into)
+    }
+
+    pub fn set_code(&mut self, name: &str, code_id: CodeId) {
+        self.codes.insert(name.into(), to_hex(code_id.as_ref()));
+    }
+
+    pub fn set_program(&mut self, name: &str, program_id: ProgramId) {
+        self.programs
+            .insert(name.into(), to_hex(program_id.as_ref()));
+    }
+
+    /// Returns the transaction id of the mint, a new one the first time it's
+    /// asked for.
+    pub fn mint_transaction_id(&mut self, mint: String) -> u64 {
+        let next_id = self.mints.values().max().map_or(0, |id| id + 1);
+        *self.mints.entry(mint).or_insert(next_id)
+    }
+}
+
+pub fn to_hex(bytes: &[u8]) -> String {
+    format!("0x{}", hex::encode(bytes))
+}
+
+pub fn from_hex(id: &str) -> Option<[u8; 32]> {
+    hex::decode(id.trim_start_matches("0x"))
+        .ok()?
+        .try_into()
+        .ok()
+}
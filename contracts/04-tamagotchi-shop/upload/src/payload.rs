@@ -0,0 +1,240 @@
+use crate::manifest::from_hex;
+use gclient::{Error, Result};
+use gear_core::ids::{CodeId, ProgramId};
+use gsdk::gp::Encode;
+use gstd::ActorId;
+use serde::Deserialize;
+use serde_yaml::Value;
+use sharded_fungible_token_io::{FTokenAction, InitFToken, LogicAction};
+use std::{collections::BTreeMap, io, path::Path};
+
+/// Program whose io types a scenario payload is encoded with.
+#[derive(Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProgramKind {
+    Ft,
+    Store,
+    Tamagotchi,
+    Army,
+    BattleStore,
+    Battle,
+    Escrow,
+    EscrowFactory,
+}
+
+impl ProgramKind {
+    /// Guesses the kind from the wasm file name, both the names of the build
+    /// output and of the files in `upload/programs` are known.
+    pub fn from_wasm_path(path: &Path) -> Option<Self> {
+        let file_name = path.file_name()?.to_str()?;
+        let kind = match file_name.strip_suffix(".opt.wasm").unwrap_or(file_name) {
+            "ft_main" | "sharded_fungible_token" => Self::Ft,
+            "store" => Self::Store,
+            "tamagotchi_shop" => Self::Tamagotchi,
+            "tamagotchi_army" => Self::Army,
+            "tamagotchi_store" => Self::BattleStore,
+            "tamagotchi_battle" => Self::Battle,
+            "escrow" => Self::Escrow,
+            "escrow_factory" => Self::EscrowFactory,
+            _ => return None,
+        };
+        Some(kind)
+    }
+}
+
+/// Values that `$code N`, `$program N` and `$account NAME` references
+/// resolve to.
+#[derive(Default)]
+pub struct References {
+    pub codes: BTreeMap<u32, CodeId>,
+    pub programs: BTreeMap<u32, ProgramId>,
+    pub accounts: BTreeMap<String, [u8; 32]>,
+}
+
+impl References {
+    pub fn encode_init(&self, kind: ProgramKind, payload: &Value) -> Result<Vec<u8>> {
+        let payload = match kind {
+            ProgramKind::Ft => InitFToken {
+                storage_code_hash: self.id(field(payload, "storage_code_hash")?)?.into(),
+                ft_logic_code_hash: self.id(field(payload, "ft_logic_code_hash")?)?.into(),
+            }
+            .encode(),
+            ProgramKind::Store => self.actor_id(payload)?.encode(),
+            ProgramKind::Tamagotchi => string(payload)?.encode(),
+            ProgramKind::Army | ProgramKind::EscrowFactory => self.id(payload)?.encode(),
+            ProgramKind::BattleStore => tamagotchi_store_io::StoreInit {
+                ft_contract_id: self.actor_id(field(payload, "ft_contract_id")?)?,
+                cost_to_upgrade_weapons: number(field(payload, "cost_to_upgrade_weapons")?)?,
+            }
+            .encode(),
+            ProgramKind::Battle => tamagotchi_battle_io::BattleInit {
+                tmg_store_id: self.actor_id(field(payload, "tmg_store_id")?)?,
+            }
+            .encode(),
+            ProgramKind::Escrow => escrow_io::InitEscrow {
+                seller: self.actor_id(field(payload, "seller")?)?,
+                buyer: self.actor_id(field(payload, "buyer")?)?,
+                price: number(field(payload, "price")?)?,
+            }
+            .encode(),
+        };
+        Ok(payload)
+    }
+
+    /// Encodes a handle payload written as `{ variantName: { fields } }`.
+    pub fn encode_message(&self, kind: ProgramKind, payload: &Value) -> Result<Vec<u8>> {
+        let (variant, fields) = variant(payload)?;
+        let payload = match (kind, variant) {
+            (ProgramKind::Ft, "message") => {
+                let (logic_variant, logic_fields) = variant(field(fields, "payload")?)?;
+                let logic_action = match logic_variant {
+                    "mint" => LogicAction::Mint {
+                        recipient: self.actor_id(field(logic_fields, "recipient")?)?,
+                        amount: number(field(logic_fields, "amount")?)?,
+                    },
+                    "transfer" => LogicAction::Transfer {
+                        sender: self.actor_id(field(logic_fields, "sender")?)?,
+                        recipient: self.actor_id(field(logic_fields, "recipient")?)?,
+                        amount: number(field(logic_fields, "amount")?)?,
+                    },
+                    "approve" => LogicAction::Approve {
+                        approved_account: self
+                            .actor_id(field(logic_fields, "approved_account")?)?,
+                        amount: number(field(logic_fields, "amount")?)?,
+                    },
+                    _ => return Err(unsupported(logic_variant)),
+                };
+                FTokenAction::Message {
+                    transaction_id: number(field(fields, "transaction_id")?)?,
+                    payload: logic_action,
+                }
+                .encode()
+            }
+            (ProgramKind::Store, "createAttribute") => store_io::StoreAction::CreateAttribute {
+                attribute_id: number(field(fields, "attribute_id")?)?,
+                attr_metadata: store_metadata(field(fields, "attr_metadata")?)?,
+                price: number(field(fields, "price")?)?,
+            }
+            .encode(),
+            (ProgramKind::Store, "setFtContractId") => store_io::StoreAction::SetFtContractId {
+                ft_contract_id: self.actor_id(field(fields, "ft_contract_id")?)?,
+            }
+            .encode(),
+            (ProgramKind::BattleStore, "createAttribute") => {
+                let metadata = field(fields, "attr_metadata")?;
+                tamagotchi_store_io::StoreAction::CreateAttribute {
+                    attribute_id: number(field(fields, "attribute_id")?)?,
+                    attribute_upgrade_id: number(field(fields, "attribute_upgrade_id")?)?,
+                    attr_metadata: tamagotchi_store_io::AttrMetadata {
+                        title: string(field(metadata, "title")?)?,
+                        description: string(field(metadata, "description")?)?,
+                        media: string(field(metadata, "media")?)?,
+                    },
+                    can_upgrade: boolean(field(fields, "can_upgrade")?)?,
+                    price: number(field(fields, "price")?)?,
+                }
+                .encode()
+            }
+            (ProgramKind::Tamagotchi, "setFTokenContract") => {
+                tamagotchi_shop_io::TmgAction::SetFTokenContract(self.actor_id(fields)?).encode()
+            }
+            _ => return Err(unsupported(variant)),
+        };
+        Ok(payload)
+    }
+
+    fn id(&self, value: &Value) -> Result<[u8; 32]> {
+        let reference = string(value)?;
+        let mut words = reference.split_whitespace();
+        let id = match (words.next(), words.next()) {
+            (Some("$code"), Some(index)) => index
+                .parse()
+                .ok()
+                .and_then(|index| self.codes.get(&index))
+                .map(|code_id| code_id.into_bytes()),
+            (Some("$program"), Some(index)) => index
+                .parse()
+                .ok()
+                .and_then(|index| self.programs.get(&index))
+                .map(|program_id| program_id.into_bytes()),
+            (Some("$account"), Some(name)) => self.accounts.get(name).copied(),
+            _ => from_hex(&reference),
+        };
+        id.ok_or_else(|| invalid(format!("`{reference}` can't be resolved")))
+    }
+
+    fn actor_id(&self, value: &Value) -> Result<ActorId> {
+        self.id(value).map(Into::into)
+    }
+}
+
+/// The attribute a `createAttribute` payload creates. Creating it twice
+/// fails, so an interrupted step is checked against the store first.
+pub fn created_attribute(payload: &Value) -> Result<Option<u128>> {
+    let (variant, fields) = variant(payload)?;
+    if variant != "createAttribute" {
+        return Ok(None);
+    }
+    number(field(fields, "attribute_id")?).map(Some)
+}
+
+fn store_metadata(value: &Value) -> Result<store_io::AttrMetadata> {
+    Ok(store_io::AttrMetadata {
+        title: string(field(value, "title")?)?,
+        description: string(field(value, "description")?)?,
+        media: string(field(value, "media")?)?,
+    })
+}
+
+fn field<'a>(value: &'a Value, name: &str) -> Result<&'a Value> {
+    value
+        .get(name)
+        .ok_or_else(|| invalid(format!("`{name}` is missing")))
+}
+
+fn variant(value: &Value) -> Result<(&str, &Value)> {
+    match value.as_mapping() {
+        Some(mapping) if mapping.len() == 1 => {
+            let (name, fields) = mapping.iter().next().expect("The mapping has an entry");
+            let name = name
+                .as_str()
+                .ok_or_else(|| invalid("An enum variant name must be a string"))?;
+            Ok((name, fields))
+        }
+        _ => Err(invalid("An enum must be written as `variant: fields`")),
+    }
+}
+
+// Numbers above `u64::MAX` don't fit a YAML integer, they can be written as
+// strings instead.
+fn number<T: TryFrom<u128>>(value: &Value) -> Result<T> {
+    let number = match value {
+        Value::Number(number) => number.as_u64().map(u128::from),
+        Value::String(number) => number.parse().ok(),
+        _ => None,
+    };
+    number
+        .and_then(|number| T::try_from(number).ok())
+        .ok_or_else(|| invalid(format!("`{value:?}` isn't a valid number")))
+}
+
+fn string(value: &Value) -> Result<String> {
+    value
+        .as_str()
+        .map(Into::into)
+        .ok_or_else(|| invalid(format!("`{value:?}` isn't a string")))
+}
+
+fn boolean(value: &Value) -> Result<bool> {
+    value
+        .as_bool()
+        .ok_or_else(|| invalid(format!("`{value:?}` isn't a boolean")))
+}
+
+fn unsupported(variant: &str) -> Error {
+    invalid(format!("`{variant}` payloads aren't supported"))
+}
+
+pub fn invalid(message: impl Into<String>) -> Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into()).into()
+}
@@ -0,0 +1,354 @@
+use crate::{
+    deploy::{salt, upload_code},
+    manifest::{from_hex, to_hex},
+    payload::{created_attribute, invalid, ProgramKind, References},
+};
+use gclient::{GearApi, Result};
+use gear_core::ids::{MessageId, ProgramId};
+use serde::{Deserialize, Serialize};
+use serde_yaml::Value;
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// A deployment description in the format of `upload/transactions.yml`.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Scenario {
+    pub ws_address: String,
+    accounts: BTreeMap<String, String>,
+    #[serde(default)]
+    codes: Vec<CodeEntry>,
+    programs: Vec<ProgramEntry>,
+    transactions: Vec<Transaction>,
+}
+
+#[derive(Deserialize)]
+struct CodeEntry {
+    name: String,
+    id: u32,
+    path_to_wasm: PathBuf,
+}
+
+#[derive(Deserialize)]
+struct ProgramEntry {
+    name: String,
+    id: u32,
+    path_to_wasm: PathBuf,
+    #[serde(default)]
+    payload: Option<Value>,
+    #[serde(default)]
+    value: u128,
+    /// The io types to encode payloads with, guessed from `path_to_wasm`
+    /// when it isn't set.
+    #[serde(default)]
+    kind: Option<ProgramKind>,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Transaction {
+    UploadCode {
+        code: u32,
+        account: String,
+    },
+    UploadProgram {
+        program: u32,
+        account: String,
+        #[serde(default)]
+        increase_gas: u64,
+    },
+    SendMessage {
+        program: u32,
+        account: String,
+        payload: Value,
+        #[serde(default)]
+        value: u128,
+        #[serde(default)]
+        increase_gas: u64,
+    },
+}
+
+/// Steps of a scenario that have already been run, so that running it
+/// again continues from the first step that didn't complete.
+#[derive(Default, Serialize, Deserialize)]
+struct Progress {
+    completed: usize,
+    codes: BTreeMap<u32, String>,
+    programs: BTreeMap<u32, String>,
+    /// The message step that was sent but not confirmed, it's reconciled
+    /// with the program state before it's sent again.
+    #[serde(default)]
+    in_flight: Option<usize>,
+}
+
+impl Progress {
+    fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let progress = serde_json::from_str(&fs::read_to_string(path)?).map_err(io::Error::from)?;
+        Ok(progress)
+    }
+
+    fn save(&self, path: &Path) -> Result<()> {
+        let progress = serde_json::to_string_pretty(self).map_err(io::Error::from)?;
+        fs::write(path, progress)?;
+        Ok(())
+    }
+}
+
+impl Scenario {
+    pub fn load(path: &Path) -> Result<Self> {
+        serde_yaml::from_str(&fs::read_to_string(path)?).map_err(|e| invalid(e.to_string()))
+    }
+
+    /// Runs the transactions in order. Wasm paths are relative to the
+    /// scenario file, the progress is kept next to it.
+    pub async fn run(&self, api: &GearApi, path: &Path) -> Result<()> {
+        let base_dir = path.parent().unwrap_or(Path::new("."));
+        let progress_path = path.with_extension("progress.json");
+        let mut progress = Progress::load(&progress_path)?;
+
+        let mut references = References::default();
+        let mut signers = BTreeMap::new();
+        for (name, suri) in &self.accounts {
+            let signer = api.clone().with(suri)?;
+            references
+                .accounts
+                .insert(name.clone(), signer.account_id().clone().into());
+            signers.insert(name.as_str(), signer);
+        }
+        for (index, code_id) in &progress.codes {
+            let code_id = from_hex(code_id).ok_or_else(|| invalid("Broken progress file"))?;
+            references.codes.insert(*index, code_id.into());
+        }
+        for (index, program_id) in &progress.programs {
+            let program_id = from_hex(program_id).ok_or_else(|| invalid("Broken progress file"))?;
+            references.programs.insert(*index, program_id.into());
+        }
+
+        if progress.completed > 0 {
+            println!(
+                "[*] Resuming after {} completed steps\n",
+                progress.completed
+            );
+        }
+        for (step, transaction) in self
+            .transactions
+            .iter()
+            .enumerate()
+            .skip(progress.completed)
+        {
+            let account = match transaction {
+                Transaction::UploadCode { account, .. }
+                | Transaction::UploadProgram { account, .. }
+                | Transaction::SendMessage { account, .. } => account,
+            };
+            let signer = signers
+                .get(account.as_str())
+                .ok_or_else(|| invalid(format!("Account `{account}` isn't defined")))?;
+
+            match transaction {
+                Transaction::UploadCode { code, .. } => {
+                    let code = self.code(*code)?;
+                    println!("[*] Upload code {}", code.name);
+                    let wasm = fs::read(base_dir.join(&code.path_to_wasm))?;
+                    let code_id = upload_code(signer, &wasm).await?;
+                    println!("    Code ID: {code_id}\n");
+
+                    references.codes.insert(code.id, code_id);
+                    progress.codes.insert(code.id, to_hex(code_id.as_ref()));
+                }
+                Transaction::UploadProgram {
+                    program,
+                    increase_gas,
+                    ..
+                } => {
+                    let program = self.program(*program)?;
+                    println!("[*] Upload {}", program.name);
+                    // the previous run created the program but stopped
+                    // before the step was marked as completed
+                    if let Some(program_id) = references.programs.get(&program.id).copied() {
+                        if signer.program_exists(program_id).await? {
+                            println!("    Program ID: {program_id}. Already created\n");
+                            progress.completed = step + 1;
+                            progress.save(&progress_path)?;
+                            continue;
+                        }
+                        // its init failed after the previous run stopped
+                        println!("    Program ID: {program_id}. Init failed, creating it again");
+                        references.programs.remove(&program.id);
+                        progress.programs.remove(&program.id);
+                    }
+                    let wasm = fs::read(base_dir.join(&program.path_to_wasm))?;
+                    let payload = match &program.payload {
+                        Some(payload) => references.encode_init(program.kind()?, payload)?,
+                        None => vec![],
+                    };
+
+                    let code_id = upload_code(signer, &wasm).await?;
+                    let gas_info = signer
+                        .calculate_create_gas(None, code_id, payload.clone(), program.value, false)
+                        .await?;
+                    let gas = increased(gas_info.min_limit, *increase_gas);
+                    println!(
+                        "    Calculated gas: {}. Applied gas: {gas}",
+                        gas_info.min_limit
+                    );
+
+                    let mut listener = signer.subscribe().await?;
+                    let (message_id, program_id, _) = signer
+                        .create_program_bytes(code_id, salt(), payload, gas, program.value)
+                        .await?;
+                    println!("    Program ID: {program_id}");
+                    references.programs.insert(program.id, program_id);
+                    progress
+                        .programs
+                        .insert(program.id, to_hex(program_id.as_ref()));
+                    progress.save(&progress_path)?;
+
+                    if !listener.message_processed(message_id).await?.succeed() {
+                        // a failed init leaves no program, the next run creates it again
+                        progress.programs.remove(&program.id);
+                        progress.save(&progress_path)?;
+                        return Err(invalid(format!("Message {message_id} failed")));
+                    }
+                    println!("    Message dispatched successfully\n");
+                }
+                Transaction::SendMessage {
+                    program,
+                    payload,
+                    value,
+                    increase_gas,
+                    ..
+                } => {
+                    let program = self.program(*program)?;
+                    let program_id =
+                        references
+                            .programs
+                            .get(&program.id)
+                            .copied()
+                            .ok_or_else(|| {
+                                invalid(format!("Program {} wasn't uploaded", program.id))
+                            })?;
+                    println!("[*] Send message to {}", program.name);
+                    if progress.in_flight == Some(step)
+                        && applied(signer, program.kind()?, program_id, payload).await?
+                    {
+                        println!("    Already applied by the previous run\n");
+                    } else {
+                        let payload = references.encode_message(program.kind()?, payload)?;
+                        progress.in_flight = Some(step);
+                        progress.save(&progress_path)?;
+
+                        send_message(signer, program_id, payload, *value, *increase_gas).await?;
+                    }
+                }
+            }
+
+            progress.completed = step + 1;
+            progress.in_flight = None;
+            progress.save(&progress_path)?;
+        }
+
+        println!("[*] Scenario completed");
+        Ok(())
+    }
+
+    fn code(&self, id: u32) -> Result<&CodeEntry> {
+        self.codes
+            .iter()
+            .find(|code| code.id == id)
+            .ok_or_else(|| invalid(format!("Code with id {id} not found")))
+    }
+
+    fn program(&self, id: u32) -> Result<&ProgramEntry> {
+        self.programs
+            .iter()
+            .find(|program| program.id == id)
+            .ok_or_else(|| invalid(format!("Program with id {id} not found")))
+    }
+}
+
+impl ProgramEntry {
+    fn kind(&self) -> Result<ProgramKind> {
+        self.kind
+            .or_else(|| ProgramKind::from_wasm_path(&self.path_to_wasm))
+            .ok_or_else(|| invalid(format!("Set the `kind` of the program {}", self.name)))
+    }
+}
+
+// Tells whether a message that was sent but not confirmed took effect. Only
+// attribute creation has to be checked: fungible token transactions are
+// executed once per transaction id and the other messages set the same value
+// again.
+async fn applied(
+    api: &GearApi,
+    kind: ProgramKind,
+    program_id: ProgramId,
+    payload: &Value,
+) -> Result<bool> {
+    let Some(attribute_id) = created_attribute(payload)? else {
+        return Ok(false);
+    };
+    let applied = match kind {
+        ProgramKind::Store => api
+            .read_state::<store_io::AttributeStore>(program_id, vec![])
+            .await?
+            .attributes
+            .contains_key(&attribute_id),
+        ProgramKind::BattleStore => {
+            let attributes = api
+                .read_state::<tamagotchi_store_io::AttributeStore>(program_id, vec![])
+                .await?
+                .attributes;
+            u32::try_from(attribute_id)
+                .is_ok_and(|attribute_id| attributes.contains_key(&attribute_id))
+        }
+        _ => false,
+    };
+    Ok(applied)
+}
+
+pub async fn send_message(
+    api: &GearApi,
+    program_id: ProgramId,
+    payload: Vec<u8>,
+    value: u128,
+    increase_gas: u64,
+) -> Result<()> {
+    let gas_info = api
+        .calculate_handle_gas(None, program_id, payload.clone(), value, false)
+        .await?;
+    let gas = increased(gas_info.min_limit, increase_gas);
+    println!(
+        "    Calculated gas: {}. Applied gas: {gas}",
+        gas_info.min_limit
+    );
+
+    let mut listener = api.subscribe().await?;
+    let (message_id, _) = api
+        .send_message_bytes(program_id, payload, gas, value)
+        .await?;
+    println!("    Message ID: {message_id}");
+    check_processed(&mut listener, message_id).await
+}
+
+async fn check_processed(
+    listener: &mut gclient::EventListener,
+    message_id: MessageId,
+) -> Result<()> {
+    if !listener.message_processed(message_id).await?.succeed() {
+        return Err(invalid(format!("Message {message_id} failed")));
+    }
+    println!("    Message dispatched successfully\n");
+    Ok(())
+}
+
+// `increase_gas` adds that many times the calculated gas, as in the JS uploader.
+fn increased(gas: u64, increase_gas: u64) -> u64 {
+    gas.saturating_add(gas.saturating_mul(increase_gas))
+}
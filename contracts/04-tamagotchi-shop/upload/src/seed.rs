@@ -0,0 +1,184 @@
+use crate::{
+    deploy::{BATTLE_STORE_WASM, FT_MAIN_WASM, STORE_WASM},
+    manifest::{from_hex, Manifest},
+    payload::invalid,
+    scenario::send_message,
+};
+use clap::ValueEnum;
+use gclient::{GearApi, Result};
+use gsdk::gp::Encode;
+use serde::Deserialize;
+use sharded_fungible_token_io::{FTokenAction, LogicAction};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs, io,
+    path::Path,
+};
+
+/// The store a catalog is created in.
+#[derive(Clone, Copy, ValueEnum)]
+pub enum StoreKind {
+    /// The `04-tamagotchi-shop` store.
+    Shop,
+    /// The `07-tamagotchi-st-new` store used by the battle.
+    Battle,
+}
+
+/// Attributes and token balances to put into a deployed stack.
+#[derive(Deserialize)]
+pub struct Catalog {
+    #[serde(default)]
+    attributes: Vec<CatalogAttribute>,
+    #[serde(default)]
+    mints: Vec<Mint>,
+}
+
+#[derive(Deserialize)]
+struct CatalogAttribute {
+    id: u32,
+    title: String,
+    description: String,
+    media: String,
+    price: u128,
+    /// The attribute this one is upgraded to, only the battle store has upgrades.
+    #[serde(default)]
+    upgrade_to: Option<u32>,
+}
+
+#[derive(Deserialize)]
+struct Mint {
+    /// A hex encoded account id or a secret URI like `//Bob`.
+    account: String,
+    amount: u128,
+}
+
+impl Catalog {
+    /// Reads a TOML catalog, or a JSON one when the file has a `.json` extension.
+    pub fn load(path: &Path) -> Result<Self> {
+        let catalog = fs::read_to_string(path)?;
+        if path
+            .extension()
+            .is_some_and(|extension| extension == "json")
+        {
+            Ok(serde_json::from_str(&catalog).map_err(io::Error::from)?)
+        } else {
+            toml::from_str(&catalog).map_err(|e| invalid(e.to_string()))
+        }
+    }
+
+    /// Creates the attributes the store doesn't have yet and mints the tokens.
+    ///
+    /// The transaction id of every mint is saved in the manifest before the
+    /// mint is sent. The fungible token executes a transaction id of a sender
+    /// only once, so seeding again doesn't mint twice.
+    pub async fn seed(
+        &self,
+        api: &GearApi,
+        manifest: &mut Manifest,
+        manifest_path: &Path,
+        store: StoreKind,
+    ) -> Result<()> {
+        let store_wasm = match store {
+            StoreKind::Shop => STORE_WASM,
+            StoreKind::Battle => BATTLE_STORE_WASM,
+        };
+        let store_id = manifest
+            .program(store_wasm)
+            .ok_or_else(|| invalid(format!("`{store_wasm}` isn't deployed")))?;
+
+        let existing: BTreeSet<u32> = match store {
+            StoreKind::Shop => api
+                .read_state::<store_io::AttributeStore>(store_id, vec![])
+                .await?
+                .attributes
+                .into_keys()
+                .filter_map(|attribute_id| attribute_id.try_into().ok())
+                .collect(),
+            StoreKind::Battle => api
+                .read_state::<tamagotchi_store_io::AttributeStore>(store_id, vec![])
+                .await?
+                .attributes
+                .into_keys()
+                .collect(),
+        };
+
+        for attribute in &self.attributes {
+            if existing.contains(&attribute.id) {
+                println!(
+                    "Attribute {} '{}' already exists, skipping\n",
+                    attribute.id, attribute.title
+                );
+                continue;
+            }
+
+            println!("Creating attribute {} '{}'", attribute.id, attribute.title);
+            send_message(api, store_id, attribute.create_action(store), 0, 0).await?;
+        }
+
+        if self.mints.is_empty() {
+            return Ok(());
+        }
+        let ft_id = manifest
+            .program(FT_MAIN_WASM)
+            .ok_or_else(|| invalid(format!("`{FT_MAIN_WASM}` isn't deployed")))?;
+        // identical mints are told apart by their occurrence in the catalog
+        let mut occurrences = BTreeMap::new();
+        for mint in &self.mints {
+            let recipient = match from_hex(&mint.account) {
+                Some(account_id) => account_id,
+                None => api.clone().with(&mint.account)?.account_id().clone().into(),
+            };
+            let occurrence = occurrences
+                .entry((recipient, mint.amount))
+                .and_modify(|occurrence| *occurrence += 1)
+                .or_insert(0);
+            let transaction_id = manifest.mint_transaction_id(format!(
+                "{}/{}/{occurrence}",
+                hex::encode(recipient),
+                mint.amount
+            ));
+            manifest.save(manifest_path)?;
+
+            println!("Minting {} tokens to {}", mint.amount, mint.account);
+            let payload = FTokenAction::Message {
+                transaction_id,
+                payload: LogicAction::Mint {
+                    recipient: recipient.into(),
+                    amount: mint.amount,
+                },
+            };
+            send_message(api, ft_id, payload.encode(), 0, 0).await?;
+        }
+
+        Ok(())
+    }
+}
+
+impl CatalogAttribute {
+    fn create_action(&self, store: StoreKind) -> Vec<u8> {
+        match store {
+            StoreKind::Shop => store_io::StoreAction::CreateAttribute {
+                attribute_id: self.id.into(),
+                attr_metadata: store_io::AttrMetadata {
+                    title: self.title.clone(),
+                    description: self.description.clone(),
+                    media: self.media.clone(),
+                },
+                price: self.price,
+            }
+            .encode(),
+            StoreKind::Battle => tamagotchi_store_io::StoreAction::CreateAttribute {
+                attribute_id: self.id,
+                attribute_upgrade_id: self.upgrade_to.unwrap_or_default(),
+                attr_metadata: tamagotchi_store_io::AttrMetadata {
+                    title: self.title.clone(),
+                    description: self.description.clone(),
+                    media: self.media.clone(),
+                },
+                can_upgrade: self.upgrade_to.is_some(),
+                price: self.price,
+            }
+            .encode(),
+        }
+    }
+}
@@ -1,10 +1,13 @@
 #![no_std]
 
 use gmeta::{In, InOut, Metadata, Out};
-use gstd::{exec, msg, prelude::*, ActorId, ReservationId};
+use gstd::{collections::BTreeMap, exec, msg, prelude::*, ActorId, MessageId, ReservationId};
+
+mod reservation;
+pub use reservation::{GasReservation, ReservationManager};
 
 use sharded_fungible_token_io::{FTokenAction, FTokenEvent, LogicAction};
-use store_io::{StoreAction, StoreEvent};
+use store_io::{StoreAction, StoreEvent, GAS_FOR_SIGNAL};
 
 pub type TransactionId = u64;
 pub type AttributeId = u32;
@@ -16,13 +19,14 @@ pub const BOREDOM_PER_BLOCK: u64 = 2;
 pub const FILL_PER_SLEEP: u64 = 1000;
 pub const FILL_PER_FEED: u64 = 1000;
 pub const FILL_PER_ENTERTAINMENT: u64 = 1000;
-
-pub struct GasReservationHandlers {
-    pub contract_send_a_delayed_message: bool,
-    pub can_send_delayed_message: bool,
-}
-
-#[derive(Default, Encode, Decode, TypeInfo)]
+pub const BLOCKS_PER_DAY: u64 = 28_800;
+pub const DEFAULT_MAX_SUBSCRIBERS: u32 = 5;
+pub const DEFAULT_WARNING_THRESHOLD: u64 = 2000;
+/// Blocks a scheduled check may arrive late before the loop is considered
+/// lost.
+pub const SCHEDULER_GRACE_BLOCKS: u64 = DELAY_OF_ONE_MINUTE as u64;
+
+#[derive(Default, Encode, Decode, TypeInfo, Clone, PartialEq, Eq)]
 #[codec(crate = gstd::codec)]
 #[scale_info(crate = gstd::scale_info)]
 pub struct Tamagotchi {
@@ -40,8 +44,149 @@ pub struct Tamagotchi {
     pub ft_contract_id: ActorId,
     pub transaction_id: TransactionId,
     pub approve_transaction: Option<(TransactionId, ActorId, u128)>,
+    pub approval_message: Option<MessageId>,
+    /// The program that created the Tamagotchi for its owner, trusted to
+    /// forward the actions of its callers.
+    pub factory: Option<ActorId>,
     // TODO: 1️⃣ Add new fields
-    pub reservations: Vec<ReservationId>,
+    pub reservations: ReservationManager,
+    pub autopilot: Option<Autopilot>,
+    pub subscribers: BTreeMap<ActorId, NeedFilter>,
+    pub max_subscribers: u32,
+    /// Blocks between two self-checks.
+    pub check_interval: u32,
+    /// Need values under which `CheckState` reports the need.
+    pub warning_thresholds: NeedThresholds,
+    pub scheduler: Scheduler,
+    pub last_check_block: u64,
+    pub keeper_pool: KeeperPool,
+    /// The state was moved to a program with newer code, this one doesn't
+    /// handle actions anymore.
+    pub exported: bool,
+}
+
+/// Rewards for the accounts that poke the Tamagotchi when a check is due.
+#[derive(Default, Encode, Decode, TypeInfo, Clone, Copy, PartialEq, Eq)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub struct KeeperPool {
+    pub native: u128,
+    pub ft: u128,
+    pub native_reward: u128,
+    pub ft_reward: u128,
+}
+
+/// State of the self-check loop.
+#[derive(Default, Encode, Decode, TypeInfo, Clone, Copy, PartialEq, Eq, Debug)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub enum Scheduler {
+    /// No check was ever scheduled.
+    #[default]
+    Idle,
+    /// The `CheckState` message the loop goes on with, expected at
+    /// `next_block`.
+    Scheduled {
+        message_id: MessageId,
+        next_block: u64,
+    },
+    /// The reservations ran out, the owner has to reserve gas again.
+    OutOfGas,
+}
+
+#[derive(Encode, Decode, TypeInfo, Clone, Copy, PartialEq, Eq, Debug)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub enum Need {
+    Feed,
+    Play,
+    Sleep,
+}
+
+impl Need {
+    pub fn event(self) -> TmgEvent {
+        match self {
+            Need::Feed => TmgEvent::FeedMe,
+            Need::Play => TmgEvent::PlayWithMe,
+            Need::Sleep => TmgEvent::WantToSleep,
+        }
+    }
+}
+
+/// Needs a subscriber wants to be notified about.
+#[derive(Default, Encode, Decode, TypeInfo, Clone, Copy, PartialEq, Eq, Debug)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub struct NeedFilter {
+    pub feed: bool,
+    pub play: bool,
+    pub sleep: bool,
+}
+
+impl NeedFilter {
+    pub fn accepts(&self, need: Need) -> bool {
+        match need {
+            Need::Feed => self.feed,
+            Need::Play => self.play,
+            Need::Sleep => self.sleep,
+        }
+    }
+}
+
+/// Need values under which the autopilot takes care of the Tamagotchi.
+#[derive(Default, Encode, Decode, TypeInfo, Clone, Copy, PartialEq, Eq)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub struct NeedThresholds {
+    pub fed: u64,
+    pub entertained: u64,
+    pub rested: u64,
+}
+
+#[derive(Default, Encode, Decode, TypeInfo, Clone, Copy, PartialEq, Eq, Debug)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub struct CareSummary {
+    pub feeds: u32,
+    pub plays: u32,
+    pub sleeps: u32,
+    /// Needs left unattended because the daily budget was spent.
+    pub skipped: u32,
+}
+
+/// Self-care done by `CheckState` messages, which are paid from the gas
+/// reservations the owner makes.
+#[derive(Default, Encode, Decode, TypeInfo, Clone, Copy, PartialEq, Eq)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub struct Autopilot {
+    pub thresholds: NeedThresholds,
+    /// Automatic actions allowed per day.
+    pub daily_budget: u32,
+    pub day_started: u64,
+    /// Automatic actions taken since `day_started`.
+    pub spent: u32,
+    pub summary: CareSummary,
+    /// Block the next summary is due at, `None` while there is no
+    /// reservation to send it from.
+    pub next_summary: Option<u64>,
+}
+
+impl Autopilot {
+    // The budget starts over on its own once a day has passed, so it doesn't
+    // depend on the summary being delivered.
+    fn try_spend(&mut self, blocks_height: u64) -> bool {
+        if blocks_height >= self.day_started + BLOCKS_PER_DAY {
+            self.day_started = blocks_height;
+            self.spent = 0;
+        }
+        if self.spent < self.daily_budget {
+            self.spent += 1;
+            return true;
+        }
+        self.summary.skipped += 1;
+        false
+    }
 }
 
 impl Tamagotchi {
@@ -148,6 +293,11 @@ impl Tamagotchi {
             (current_transaction_id, account, amount)
         };
 
+        // If the message fails while waiting for the approval, the signal
+        // drops `approve_transaction`
+        exec::system_reserve_gas(GAS_FOR_SIGNAL).expect("Error in reserving gas for a signal");
+        self.approval_message = Some(msg::id());
+
         let result_transaction: FTokenEvent = msg::send_for_reply_as(
             self.ft_contract_id,
             FTokenAction::Message {
@@ -174,37 +324,359 @@ impl Tamagotchi {
         msg::reply(response, 0).expect("Error in sending a reply `TmgEvent::ApprovalError`");
     }
 
-    pub fn make_reservation(&mut self, reservation_amount: u64, reservation_duration: u32) {
-        let reservation_id = ReservationId::reserve(reservation_amount, reservation_duration)
-            .expect("reservation across executions");
+    /// Takes care of the needs under the autopilot thresholds within the
+    /// daily budget.
+    pub fn run_autopilot(&mut self) {
+        let Some(mut autopilot) = self.autopilot else {
+            return;
+        };
 
-        self.reservations.push(reservation_id);
+        let blocks_height = blocks_height();
+        let thresholds = autopilot.thresholds;
+        if self.updated_feed_value(blocks_height) < thresholds.fed
+            && autopilot.try_spend(blocks_height)
+        {
+            self.feed();
+            autopilot.summary.feeds += 1;
+        }
+        if self.updated_play_value(blocks_height) < thresholds.entertained
+            && autopilot.try_spend(blocks_height)
+        {
+            self.play();
+            autopilot.summary.plays += 1;
+        }
+        if self.updated_sleep_value(blocks_height) < thresholds.rested
+            && autopilot.try_spend(blocks_height)
+        {
+            self.sleep();
+            autopilot.summary.sleeps += 1;
+        }
+
+        self.autopilot = Some(autopilot);
     }
 
-    pub fn check_state_of_tamagotchi(&mut self) {
-        let Some(reservation_id) = self.reservations.pop() else {
-            panic!("Error getting reservation id");
+    /// Schedules the daily summary of the autopilot from a reservation, so
+    /// the owner gets it even on days no check runs.
+    pub fn schedule_autopilot_summary(&mut self) {
+        let Some(autopilot) = self.autopilot.as_mut() else {
+            return;
+        };
+        let Some(reservation_id) = self.reservations.take() else {
+            autopilot.next_summary = None;
+            return;
         };
 
         msg::send_delayed_from_reservation(
+            reservation_id,
+            exec::program_id(),
+            TmgAction::SendAutopilotSummary,
+            0,
+            BLOCKS_PER_DAY as u32,
+        )
+        .expect("Error sending message from reservation");
+        autopilot.next_summary = Some(blocks_height() + BLOCKS_PER_DAY);
+    }
+
+    /// Sends the owner the care since the previous summary. It's called
+    /// from a `SendAutopilotSummary` message, so the delivery is paid from
+    /// the reservation that message was sent from.
+    pub fn send_autopilot_summary(&mut self) {
+        let blocks_height = blocks_height();
+        let Some(autopilot) = self.autopilot.as_mut() else {
+            return;
+        };
+        // A summary scheduled before the autopilot was enabled again is
+        // due earlier than the current one
+        if autopilot
+            .next_summary
+            .map_or(true, |next_summary| next_summary > blocks_height)
+        {
+            return;
+        }
+
+        msg::send(self.owner, TmgEvent::AutopilotSummary(autopilot.summary), 0)
+            .expect("Error in sending a message `TmgEvent::AutopilotSummary`");
+        autopilot.summary = CareSummary::default();
+        self.schedule_autopilot_summary();
+    }
+
+    /// Every need whose value is under its warning threshold.
+    pub fn needs(&self) -> Vec<Need> {
+        let blocks_height = blocks_height();
+        let thresholds = self.warning_thresholds;
+        let mut needs = Vec::new();
+        if self.updated_feed_value(blocks_height) < thresholds.fed {
+            needs.push(Need::Feed);
+        }
+        if self.updated_play_value(blocks_height) < thresholds.entertained {
+            needs.push(Need::Play);
+        }
+        if self.updated_sleep_value(blocks_height) < thresholds.rested {
+            needs.push(Need::Sleep);
+        }
+        needs
+    }
+
+    /// Sends the needs to the subscribers and the owner, or lets the
+    /// autopilot take care of them.
+    pub fn report_needs(&mut self, needs: Vec<Need>) {
+        self.last_check_block = blocks_height();
+        for need in &needs {
+            self.notify_subscribers(*need);
+        }
+        if self.autopilot.is_some() {
+            // The autopilot takes care of the needs itself and
+            // reports to the owner once a day.
+            self.run_autopilot();
+        } else if !needs.is_empty() {
+            msg::send(self.owner, TmgEvent::Needs(needs), 0)
+                .expect("Error in sending a message `TmgEvent::Needs`");
+        }
+    }
+
+    pub fn is_check_due(&self) -> bool {
+        blocks_height() >= self.last_check_block + self.check_interval as u64
+    }
+
+    pub async fn transfer_tokens(
+        &mut self,
+        sender: ActorId,
+        recipient: ActorId,
+        amount: u128,
+    ) -> bool {
+        let transaction_id = self.transaction_id;
+        self.transaction_id = self.transaction_id.wrapping_add(1);
+
+        let result_transaction = msg::send_for_reply_as::<_, FTokenEvent>(
+            self.ft_contract_id,
+            FTokenAction::Message {
+                transaction_id,
+                payload: LogicAction::Transfer {
+                    sender,
+                    recipient,
+                    amount,
+                },
+            },
+            0,
+            0,
+        )
+        .expect("Error in sending a message `FTokenAction::Message`")
+        .await;
+
+        matches!(result_transaction, Ok(FTokenEvent::Ok))
+    }
+
+    pub async fn fund_keeper_pool(&mut self, ft_amount: u128) {
+        let native = msg::value();
+        if ft_amount > 0
+            && !self
+                .transfer_tokens(self.owner, exec::program_id(), ft_amount)
+                .await
+        {
+            panic!("Error in transferring tokens to the keeper pool");
+        }
+
+        self.keeper_pool.native += native;
+        self.keeper_pool.ft += ft_amount;
+        msg::reply(
+            TmgEvent::KeeperPoolFunded {
+                native,
+                ft: ft_amount,
+            },
+            0,
+        )
+        .expect("Error in sending a reply `TmgEvent::KeeperPoolFunded`");
+    }
+
+    /// Runs a check on behalf of the scheduler and rewards the keeper.
+    pub async fn poke(&mut self, keeper: ActorId) {
+        // The check is recorded before any await, so a second poke in
+        // the same interval is rejected
+        assert!(self.is_check_due(), "The check interval hasn't elapsed yet");
+        self.report_needs(self.needs());
+
+        let native_reward = self.keeper_pool.native_reward.min(self.keeper_pool.native);
+        self.keeper_pool.native -= native_reward;
+
+        let mut ft_reward = self.keeper_pool.ft_reward.min(self.keeper_pool.ft);
+        if ft_reward > 0 {
+            self.keeper_pool.ft -= ft_reward;
+            if !self
+                .transfer_tokens(exec::program_id(), keeper, ft_reward)
+                .await
+            {
+                self.keeper_pool.ft += ft_reward;
+                ft_reward = 0;
+            }
+        }
+
+        // A poke forwarded by the factory pays the keeper, not the factory
+        let reply_value = if keeper == msg::source() {
+            native_reward
+        } else {
+            msg::send(
+                keeper,
+                TmgEvent::Poked {
+                    native_reward,
+                    ft_reward,
+                },
+                native_reward,
+            )
+            .expect("Error in sending a message `TmgEvent::Poked`");
+            0
+        };
+        msg::reply(
+            TmgEvent::Poked {
+                native_reward,
+                ft_reward,
+            },
+            reply_value,
+        )
+        .expect("Error in sending a reply `TmgEvent::Poked`");
+    }
+
+    /// Hands the state over to the factory, which migrates it to a program
+    /// with newer code. The native keeper pool goes along with the reply and
+    /// the tokens of the pool are returned to the owner.
+    ///
+    /// If the migration fails after the export, the factory exports the
+    /// Tamagotchi again and gets the same state. The native keeper pool was
+    /// already sent with the first reply, so it isn't sent again.
+    pub async fn export(&mut self, caller: ActorId) {
+        assert!(
+            self.factory == Some(msg::source()) && self.owner == caller,
+            "Only the owner can migrate the Tamagotchi through its factory"
+        );
+        if self.exported {
+            msg::reply(TmgEvent::Exported(self.clone()), 0)
+                .expect("Error in sending a reply `TmgEvent::Exported`");
+            return;
+        }
+
+        if self.keeper_pool.ft > 0 {
+            let ft = self.keeper_pool.ft;
+            if !self
+                .transfer_tokens(exec::program_id(), self.owner, ft)
+                .await
+            {
+                panic!("Error in returning the keeper pool tokens");
+            }
+            self.keeper_pool.ft = 0;
+        }
+
+        // Set after the last await, a failed refund leaves the Tamagotchi
+        // working in this program
+        self.exported = true;
+        msg::reply(TmgEvent::Exported(self.clone()), self.keeper_pool.native)
+            .expect("Error in sending a reply `TmgEvent::Exported`");
+    }
+
+    /// The state exported from the previous program, without what was bound
+    /// to it: reservations, the scheduler loop, the scheduled autopilot
+    /// summary and pending approvals.
+    pub fn imported(self, factory: Option<ActorId>) -> Self {
+        Tamagotchi {
+            factory,
+            autopilot: self.autopilot.map(|autopilot| Autopilot {
+                next_summary: None,
+                ..autopilot
+            }),
+            approve_transaction: None,
+            approval_message: None,
+            reservations: ReservationManager::default(),
+            scheduler: Scheduler::Idle,
+            exported: false,
+            ..self
+        }
+    }
+
+    pub fn subscribe(&mut self, subscriber: ActorId, filter: NeedFilter) {
+        if !self.subscribers.contains_key(&subscriber) {
+            assert!(
+                (self.subscribers.len() as u32) < self.max_subscribers,
+                "The subscriber limit has been reached"
+            );
+        }
+        self.subscribers.insert(subscriber, filter);
+    }
+
+    /// Sends the need to every subscriber whose filter accepts it. It's
+    /// called from a `CheckState` message, so the delivery is paid from the
+    /// reservation that message was sent from.
+    pub fn notify_subscribers(&self, need: Need) {
+        for (subscriber, filter) in &self.subscribers {
+            if filter.accepts(need) {
+                msg::send(*subscriber, need.event(), 0)
+                    .expect("Error in sending a need to a subscriber");
+            }
+        }
+    }
+
+    /// An approval only sets the allowance, so it's safe to send it again
+    /// with a new transaction id.
+    pub fn handle_signal(&mut self, failed: MessageId) {
+        if self.approval_message == Some(failed) {
+            self.approve_transaction = None;
+            self.approval_message = None;
+        }
+    }
+
+    pub fn make_reservation(&mut self, reservation_amount: u64, reservation_duration: u32) {
+        self.reservations
+            .reserve(reservation_amount, reservation_duration);
+    }
+
+    pub fn check_state_of_tamagotchi(&mut self) {
+        let Some(reservation_id) = self.reservations.take() else {
+            self.scheduler = Scheduler::OutOfGas;
+            return;
+        };
+
+        let message_id = msg::send_delayed_from_reservation(
             reservation_id,
             exec::program_id(),
             TmgAction::CheckState,
             0,
-            DELAY_OF_ONE_MINUTE,
+            self.check_interval,
         )
         .expect("Error sending message from reservation");
+
+        self.scheduler = Scheduler::Scheduled {
+            message_id,
+            next_block: blocks_height() + self.check_interval as u64,
+        };
+    }
+
+    /// Whether a check is pending. A scheduled check that hasn't arrived
+    /// within `SCHEDULER_GRACE_BLOCKS` after its block was lost, so the loop
+    /// isn't running anymore.
+    pub fn is_scheduler_running(&self) -> bool {
+        match self.scheduler {
+            Scheduler::Scheduled { next_block, .. } => {
+                next_block + SCHEDULER_GRACE_BLOCKS >= blocks_height()
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether the message is the check the loop goes on with. A check of a
+    /// loop that was replaced arrives late and is ignored.
+    pub fn is_scheduled_check(&self, message_id: MessageId) -> bool {
+        matches!(
+            self.scheduler,
+            Scheduler::Scheduled { message_id: scheduled, .. } if scheduled == message_id
+        )
     }
 
     pub fn send_delayed_message_with_reservation_to_owner(&mut self, payload: TmgEvent) {
-        self.send_delayed_message_from_reservation(self.owner, payload, DELAY_OF_ONE_MINUTE);
+        self.send_delayed_message_from_reservation(self.owner, payload, self.check_interval);
     }
 
     pub fn send_delayed_make_reservation_message_to_owner(&mut self) {
         self.send_delayed_message_from_reservation(
             self.owner,
             TmgEvent::MakeReservation,
-            DELAY_OF_ONE_MINUTE,
+            self.check_interval,
         );
     }
 
@@ -214,7 +686,7 @@ impl Tamagotchi {
         payload: TmgEvent,
         delay: u32,
     ) {
-        let Some(reservation_id) = self.reservations.pop() else {
+        let Some(reservation_id) = self.reservations.take() else {
             panic!("Error getting reservation id");
         };
 
@@ -251,7 +723,43 @@ pub enum TmgAction {
         reservation_amount: u64,
         duration: u32,
     },
-    TmgInfo
+    TmgInfo,
+    EnableAutopilot {
+        thresholds: NeedThresholds,
+        daily_budget: u32,
+    },
+    DisableAutopilot,
+    Unreserve(ReservationId),
+    Reservations,
+    Subscribe {
+        subscriber: ActorId,
+        filter: NeedFilter,
+    },
+    Unsubscribe(ActorId),
+    SetMaxSubscribers(u32),
+    SetCheckInterval(u32),
+    SetWarningThresholds(NeedThresholds),
+    RestartScheduler,
+    /// The attached value and `ft_amount` tokens of the owner go to the
+    /// keeper pool.
+    FundKeeperPool {
+        ft_amount: u128,
+    },
+    SetKeeperReward {
+        native: u128,
+        ft: u128,
+    },
+    Poke,
+    /// Sent by the factory to migrate the Tamagotchi to newer code.
+    Export,
+    /// An action the factory forwards on behalf of `caller`.
+    Delegated {
+        caller: ActorId,
+        action: Box<TmgAction>,
+    },
+    /// Sent by the Tamagotchi to itself once a day while the autopilot is
+    /// enabled.
+    SendAutopilotSummary,
 }
 
 #[derive(Encode, Decode, TypeInfo, Eq, PartialEq)]
@@ -268,7 +776,10 @@ pub enum TmgEvent {
     Approved(ActorId),
     ApprovalRevoked,
     FTokenContractSet,
-    TokensApproved { account: ActorId, amount: u128 },
+    TokensApproved {
+        account: ActorId,
+        amount: u128,
+    },
     ApprovalError,
     AttributeBought(AttributeId),
     CompletePrevPurchase(AttributeId),
@@ -280,7 +791,33 @@ pub enum TmgEvent {
     AllGood, // extra field to return if the user check state
     MakeReservation,
     GasReserved,
-    Owner(ActorId)
+    Owner(ActorId),
+    AutopilotEnabled,
+    AutopilotDisabled,
+    AutopilotSummary(CareSummary),
+    Unreserved {
+        amount: u64,
+    },
+    Reservations(Vec<GasReservation>),
+    Subscribed(ActorId),
+    Unsubscribed(ActorId),
+    MaxSubscribersSet(u32),
+    Needs(Vec<Need>),
+    CheckIntervalSet(u32),
+    WarningThresholdsSet,
+    SchedulerRestarted {
+        next_block: u64,
+    },
+    KeeperPoolFunded {
+        native: u128,
+        ft: u128,
+    },
+    KeeperRewardSet,
+    Poked {
+        native_reward: u128,
+        ft_reward: u128,
+    },
+    Exported(Tamagotchi),
 }
 
 #[derive(Encode, Decode, TypeInfo)]
@@ -289,6 +826,11 @@ pub enum TmgEvent {
 pub struct TmgInit {
     pub owner: ActorId,
     pub name: String,
+    /// The program creating the Tamagotchi, trusted to forward the actions
+    /// of its callers. Only the creating program can set itself.
+    pub factory: Option<ActorId>,
+    /// The state of a Tamagotchi migrated from older code.
+    pub imported: Option<Tamagotchi>,
 }
 
 pub struct ProgramMetadata;
@@ -0,0 +1,83 @@
+use gstd::{exec, prelude::*, ReservationId};
+
+#[derive(Encode, Decode, TypeInfo, Clone, Copy, PartialEq, Eq, Debug)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub struct GasReservation {
+    pub id: ReservationId,
+    pub amount: u64,
+    pub expires_at_block: u32,
+}
+
+impl GasReservation {
+    pub fn is_expired(&self) -> bool {
+        exec::block_height() >= self.expires_at_block
+    }
+}
+
+/// Gas reserved by a program for its delayed messages.
+///
+/// Expired reservations can't be used or unreserved anymore, so they are
+/// skipped and dropped instead of being handed out.
+#[derive(Default, Encode, Decode, TypeInfo, Clone, PartialEq, Eq)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub struct ReservationManager {
+    reservations: Vec<GasReservation>,
+}
+
+impl ReservationManager {
+    pub fn reserve(&mut self, amount: u64, duration: u32) -> GasReservation {
+        let id = ReservationId::reserve(amount, duration).expect("reservation across executions");
+        let reservation = GasReservation {
+            id,
+            amount,
+            expires_at_block: exec::block_height().saturating_add(duration),
+        };
+        self.reservations.push(reservation);
+        reservation
+    }
+
+    /// Takes the most recent reservation that hasn't expired yet.
+    pub fn take(&mut self) -> Option<ReservationId> {
+        self.drop_expired();
+        self.reservations.pop().map(|reservation| reservation.id)
+    }
+
+    /// Returns the reserved gas to the program and the amount recovered.
+    pub fn unreserve(&mut self, id: ReservationId) -> u64 {
+        self.drop_expired();
+        let position = self
+            .reservations
+            .iter()
+            .position(|reservation| reservation.id == id)
+            .expect("The reservation doesn't exist or has expired");
+        self.reservations.remove(position);
+        id.unreserve().expect("Error in unreserving gas")
+    }
+
+    /// Reservations that can still be used.
+    pub fn active(&self) -> Vec<GasReservation> {
+        self.reservations
+            .iter()
+            .filter(|reservation| !reservation.is_expired())
+            .copied()
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.reservations
+            .iter()
+            .filter(|reservation| !reservation.is_expired())
+            .count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn drop_expired(&mut self) {
+        self.reservations
+            .retain(|reservation| !reservation.is_expired());
+    }
+}
@@ -6,14 +6,25 @@ use tamagotchi_auto_io::*;
 
 static mut TAMAGOTCHI: Option<Tamagotchi> = None;
 
-// extra state to handle backup gas and delayed messages,
-//separated from the main state so as not to affect the flow of tasks
-static mut GAS_RESERVATIONS_HANDLERS: Option<GasReservationHandlers> = None;
-
 #[no_mangle]
 extern fn init() {
     // TODO: 0️⃣ Copy the `init` function from the previous lesson and push changes to the master branch
-    let TmgInit { owner, name } = msg::load().expect("Error in init message");
+    let TmgInit {
+        owner,
+        name,
+        factory,
+        imported,
+    } = msg::load().expect("Error in init message");
+    assert!(
+        factory.map_or(true, |factory| factory == msg::source()),
+        "Only the creating program can be the factory"
+    );
+    if let Some(tamagotchi) = imported {
+        unsafe { TAMAGOTCHI = Some(tamagotchi.imported(factory)) };
+        msg::reply("successful initialization!", 0).expect("error in reply");
+        return;
+    }
+
     let block_height = blocks_height();
     let new_tamagotchi: Tamagotchi = Tamagotchi {
         name,
@@ -26,29 +37,43 @@ extern fn init() {
         rested: 5000,
         rested_block: block_height,
         approved_account: None,
+        factory,
+        max_subscribers: DEFAULT_MAX_SUBSCRIBERS,
+        check_interval: DELAY_OF_ONE_MINUTE,
+        last_check_block: block_height,
+        warning_thresholds: NeedThresholds {
+            fed: DEFAULT_WARNING_THRESHOLD,
+            entertained: DEFAULT_WARNING_THRESHOLD,
+            rested: DEFAULT_WARNING_THRESHOLD,
+        },
         ..Tamagotchi::default()
     };
-    unsafe {
-        TAMAGOTCHI = Some(new_tamagotchi);
-        GAS_RESERVATIONS_HANDLERS = Some(GasReservationHandlers {
-            contract_send_a_delayed_message: false,
-            can_send_delayed_message: false,
-        });
-    };
+    unsafe { TAMAGOTCHI = Some(new_tamagotchi) };
     msg::reply("successful initialization!", 0).expect("error in reply");
 }
 
-#[gstd::async_main]
+#[gstd::async_main(handle_signal = signal)]
 async fn main() {
     // TODO: 0️⃣ Copy the `handle` function from the previous lesson and push changes to the master branch
     let type_message: TmgAction = msg::load().expect("error in load message");
 
     let tamagotchi = state_mut();
-    let GasReservationHandlers {
-        can_send_delayed_message,
-        contract_send_a_delayed_message,
-    } = handlers_state_mut();
-    let caller = msg::source();
+    let (caller, type_message) = match type_message {
+        TmgAction::Delegated { caller, action } => {
+            assert_eq!(
+                tamagotchi.factory,
+                Some(msg::source()),
+                "Only the factory can forward actions"
+            );
+            (caller, *action)
+        }
+        action => (msg::source(), action),
+    };
+    // A migration that failed after the export exports the Tamagotchi again
+    assert!(
+        !tamagotchi.exported || matches!(type_message, TmgAction::Export),
+        "The Tamagotchi has been migrated to a new program"
+    );
 
     match type_message {
         TmgAction::Name => {
@@ -113,46 +138,43 @@ async fn main() {
         }
         // TODO; 6️⃣ Add handling new actions
         TmgAction::CheckState => {
-            let payload;
-
             // this only check the state of the tamagotchi, does not change
             // the state of the contract
-
-            let blocks_height = blocks_height();
-            if tamagotchi.updated_feed_value(blocks_height) == 1 {
-                // tamagotchi.send_message(TmgEvent::FeedMe);
-                payload = TmgEvent::FeedMe;
-            } else if tamagotchi.updated_play_value(blocks_height) == 1 {
-                //tamagotchi.send_message(TmgEvent::PlayWithMe);
-                payload = TmgEvent::PlayWithMe;
-            } else if tamagotchi.updated_sleep_value(blocks_height) == 1 {
-                // tamagotchi.send_message(TmgEvent::WantToSleep);
-                payload = TmgEvent::WantToSleep;
-            } else {
-                payload = TmgEvent::AllGood;
-            }
+            let needs = tamagotchi.needs();
 
             // If the address is not the same as that of the contract,
-            // only the payload obtained is forwarded
+            // only the needs obtained are forwarded
             if exec::program_id() != caller {
+                let payload = if needs.is_empty() {
+                    TmgEvent::AllGood
+                } else {
+                    TmgEvent::Needs(needs)
+                };
                 msg::reply(payload, 0).expect("Error in reply");
                 return;
             }
 
-            if tamagotchi.reservations.len() == 1 {
+            // A late check of a loop that was restarted meanwhile would run
+            // a second loop
+            if !tamagotchi.is_scheduled_check(msg::id()) {
+                return;
+            }
+
+            if tamagotchi.reservations.is_empty() {
+                // Every reservation has expired, the checks stop until the
+                // owner reserves gas again.
+                tamagotchi.scheduler = Scheduler::OutOfGas;
+            } else if tamagotchi.reservations.len() == 1 {
                 // If there is only one gas reserve left, it is used to
                 // notify the owner to make more gas reserves.
-                *can_send_delayed_message = false;
-                *contract_send_a_delayed_message = false;
                 tamagotchi.send_delayed_make_reservation_message_to_owner();
+                tamagotchi.scheduler = Scheduler::OutOfGas;
             } else {
                 // If the tamagotchi has needs, it is sent to the user, and
                 // it calls itself again to make a new review.
-                if let TmgEvent::AllGood = payload {
-                    // A normal message is sent to the owner, since the
-                    // gas that was previously required with the reservation is used.
-                    msg::send(tamagotchi.owner, payload, 0).expect("error sending message");
-                }
+                // A normal message is sent to the owner, since the
+                // gas that was previously required with the reservation is used.
+                tamagotchi.report_needs(needs);
                 tamagotchi.check_state_of_tamagotchi();
             }
         }
@@ -162,30 +184,155 @@ async fn main() {
         } => {
             tamagotchi.make_reservation(reservation_amount, duration);
 
-            // It is checked that there are three or more reservation IDs,
-            // so that the contract can send the message that it ran out of reserve gas
-            if tamagotchi.reservations.len() >= 3 {
-                *can_send_delayed_message = true;
-            }
-
-            // If the contract can already send the message, and it has not yet
-            // sent the message, the message is sent, preventing more than one
+            // The loop starts once there are three or more reservation IDs,
+            // so that the contract can send the message that it ran out of reserve gas.
+            // A running loop isn't started again, preventing more than one
             // message at a time by adding more gas reserves
-            if *can_send_delayed_message && !(*contract_send_a_delayed_message) {
-                // set to true to prevent more than one delayed message at a time
-                *contract_send_a_delayed_message = true;
+            if !tamagotchi.is_scheduler_running() && tamagotchi.reservations.len() >= 3 {
                 tamagotchi.check_state_of_tamagotchi();
             }
+            // The summaries of the autopilot stop when the reservations run
+            // out and go on with the new one
+            if matches!(
+                tamagotchi.autopilot,
+                Some(Autopilot {
+                    next_summary: None,
+                    ..
+                })
+            ) {
+                tamagotchi.schedule_autopilot_summary();
+            }
 
             msg::reply(TmgEvent::GasReserved, 0).expect("Error in sending a reply");
         }
         TmgAction::TmgInfo => {
-            msg::reply(TmgEvent::Owner(tamagotchi.owner), 0) 
-                .expect("Error in reply");
+            msg::reply(TmgEvent::Owner(tamagotchi.owner), 0).expect("Error in reply");
+        }
+        TmgAction::EnableAutopilot {
+            thresholds,
+            daily_budget,
+        } => {
+            if tamagotchi.owner == caller {
+                match tamagotchi.autopilot.as_mut() {
+                    // New settings keep the day and the scheduled summary
+                    Some(autopilot) => {
+                        autopilot.thresholds = thresholds;
+                        autopilot.daily_budget = daily_budget;
+                    }
+                    None => {
+                        tamagotchi.autopilot = Some(Autopilot {
+                            thresholds,
+                            daily_budget,
+                            day_started: blocks_height(),
+                            spent: 0,
+                            summary: CareSummary::default(),
+                            next_summary: None,
+                        });
+                        tamagotchi.schedule_autopilot_summary();
+                    }
+                }
+                msg::reply(TmgEvent::AutopilotEnabled, 0).expect("Error in sending reply");
+            }
+        }
+        TmgAction::DisableAutopilot => {
+            if tamagotchi.owner == caller {
+                tamagotchi.autopilot = None;
+                msg::reply(TmgEvent::AutopilotDisabled, 0).expect("Error in sending reply");
+            }
+        }
+        TmgAction::Unreserve(reservation_id) => {
+            if tamagotchi.owner == caller {
+                let amount = tamagotchi.reservations.unreserve(reservation_id);
+                msg::reply(TmgEvent::Unreserved { amount }, 0).expect("Error in sending reply");
+            }
+        }
+        TmgAction::Reservations => {
+            msg::reply(TmgEvent::Reservations(tamagotchi.reservations.active()), 0)
+                .expect("Error in sending reply");
+        }
+        TmgAction::Subscribe { subscriber, filter } => {
+            if tamagotchi.is_owner_or_approved(&caller) {
+                tamagotchi.subscribe(subscriber, filter);
+                msg::reply(TmgEvent::Subscribed(subscriber), 0).expect("Error in sending reply");
+            }
+        }
+        TmgAction::Unsubscribe(subscriber) => {
+            // A subscriber can always leave by itself
+            if tamagotchi.is_owner_or_approved(&caller) || subscriber == caller {
+                tamagotchi.subscribers.remove(&subscriber);
+                msg::reply(TmgEvent::Unsubscribed(subscriber), 0).expect("Error in sending reply");
+            }
+        }
+        TmgAction::SetCheckInterval(check_interval) => {
+            if tamagotchi.owner == caller {
+                assert!(check_interval > 0, "The check interval must be positive");
+                tamagotchi.check_interval = check_interval;
+                msg::reply(TmgEvent::CheckIntervalSet(check_interval), 0)
+                    .expect("Error in sending reply");
+            }
+        }
+        TmgAction::SetWarningThresholds(thresholds) => {
+            if tamagotchi.owner == caller {
+                tamagotchi.warning_thresholds = thresholds;
+                msg::reply(TmgEvent::WarningThresholdsSet, 0).expect("Error in sending reply");
+            }
+        }
+        TmgAction::RestartScheduler => {
+            if tamagotchi.owner == caller {
+                assert!(
+                    !tamagotchi.is_scheduler_running(),
+                    "The scheduler is already running"
+                );
+                tamagotchi.check_state_of_tamagotchi();
+                let Scheduler::Scheduled { next_block, .. } = tamagotchi.scheduler else {
+                    panic!("There are no gas reservations to restart the scheduler");
+                };
+                msg::reply(TmgEvent::SchedulerRestarted { next_block }, 0)
+                    .expect("Error in sending reply");
+            }
+        }
+        TmgAction::FundKeeperPool { ft_amount } => {
+            if tamagotchi.owner != caller {
+                panic!("Only the owner can fund the keeper pool");
+            }
+            tamagotchi.fund_keeper_pool(ft_amount).await;
+        }
+        TmgAction::SetKeeperReward { native, ft } => {
+            if tamagotchi.owner == caller {
+                tamagotchi.keeper_pool.native_reward = native;
+                tamagotchi.keeper_pool.ft_reward = ft;
+                msg::reply(TmgEvent::KeeperRewardSet, 0).expect("Error in sending reply");
+            }
+        }
+        TmgAction::Poke => {
+            tamagotchi.poke(caller).await;
+        }
+        TmgAction::Export => {
+            tamagotchi.export(caller).await;
+        }
+        TmgAction::Delegated { .. } => {
+            panic!("A forwarded action can't be forwarded again");
+        }
+        TmgAction::SendAutopilotSummary => {
+            if exec::program_id() == caller {
+                tamagotchi.send_autopilot_summary();
+            }
+        }
+        TmgAction::SetMaxSubscribers(max_subscribers) => {
+            if tamagotchi.owner == caller {
+                tamagotchi.max_subscribers = max_subscribers;
+                msg::reply(TmgEvent::MaxSubscribersSet(max_subscribers), 0)
+                    .expect("Error in sending reply");
+            }
         }
     }
 }
 
+fn signal() {
+    let failed = msg::signal_from().expect("Error in getting the failed message");
+    state_mut().handle_signal(failed);
+}
+
 #[no_mangle]
 extern fn state() {
     // TODO: 0️⃣ Copy the `handle` function from the previous lesson and push changes to the master branch
@@ -203,9 +350,3 @@ fn state_mut() -> &'static mut Tamagotchi {
     debug_assert!(state.is_some(), "State is not initialized");
     unsafe { state.unwrap_unchecked() }
 }
-
-fn handlers_state_mut() -> &'static mut GasReservationHandlers {
-    let state = unsafe { GAS_RESERVATIONS_HANDLERS.as_mut() };
-    debug_assert!(state.is_some(), "State is not initialized");
-    unsafe { state.unwrap_unchecked() }
-}
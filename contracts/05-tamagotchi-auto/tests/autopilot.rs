@@ -0,0 +1,143 @@
+mod common;
+
+use common::{init_tamagotchi, reservations, tamagotchi_state, OWNER, USER};
+use gtest::{Log, Program, System};
+use tamagotchi_auto_io::{
+    CareSummary, NeedThresholds, TmgAction, TmgEvent, BLOCKS_PER_DAY, DELAY_OF_ONE_MINUTE,
+};
+
+// The next summary takes its reservation a day later
+const RESERVATION_DURATION: u32 = 2 * BLOCKS_PER_DAY as u32;
+
+fn reserve_gas(tamagotchi: &Program<'_>) {
+    common::reserve_gas(tamagotchi, RESERVATION_DURATION);
+}
+
+fn enable_autopilot(tamagotchi: &Program<'_>) {
+    let res = tamagotchi.send(
+        OWNER,
+        TmgAction::EnableAutopilot {
+            thresholds: NeedThresholds::default(),
+            daily_budget: 10,
+        },
+    );
+    let log = Log::builder()
+        .dest(OWNER)
+        .payload(TmgEvent::AutopilotEnabled);
+    assert!(res.contains(&log));
+}
+
+fn next_summary(tamagotchi: &Program<'_>) -> Option<u64> {
+    tamagotchi_state(tamagotchi)
+        .autopilot
+        .expect("The autopilot isn't enabled")
+        .next_summary
+}
+
+fn summary_log() -> Log {
+    Log::builder()
+        .dest(OWNER)
+        .payload(TmgEvent::AutopilotSummary(CareSummary::default()))
+}
+
+#[test]
+fn summary_is_sent_without_checks() {
+    let sys = System::new();
+    sys.init_logger();
+    let tamagotchi = init_tamagotchi(&sys);
+
+    // without reservations there is nothing to send the summary from
+    enable_autopilot(&tamagotchi);
+    assert_eq!(next_summary(&tamagotchi), None);
+
+    // a single reservation doesn't start the check loop, but the summary
+    // is scheduled from it
+    reserve_gas(&tamagotchi);
+    assert!(next_summary(&tamagotchi).is_some());
+
+    let results = sys.spend_blocks(BLOCKS_PER_DAY as u32);
+    assert!(results.iter().any(|res| res.contains(&summary_log())));
+
+    // the reservations ran out, the next one schedules the summary again
+    assert_eq!(next_summary(&tamagotchi), None);
+    reserve_gas(&tamagotchi);
+    assert!(next_summary(&tamagotchi).is_some());
+}
+
+#[test]
+fn only_the_scheduled_summary_is_sent() {
+    let sys = System::new();
+    sys.init_logger();
+    let tamagotchi = init_tamagotchi(&sys);
+    reserve_gas(&tamagotchi);
+    reserve_gas(&tamagotchi);
+
+    // only the Tamagotchi itself can send the summary
+    let res = tamagotchi.send(USER, TmgAction::SendAutopilotSummary);
+    assert!(!res.main_failed());
+    assert!(!res.contains(&summary_log()));
+
+    // new settings keep the scheduled summary and take no reservation
+    enable_autopilot(&tamagotchi);
+    let first_block = next_summary(&tamagotchi).expect("The summary isn't scheduled");
+    sys.spend_blocks(BLOCKS_PER_DAY as u32 / 2);
+    enable_autopilot(&tamagotchi);
+    assert_eq!(next_summary(&tamagotchi), Some(first_block));
+    assert_eq!(reservations(&tamagotchi).len(), 1);
+
+    // the summary scheduled before enabling the autopilot again is ignored
+    let res = tamagotchi.send(OWNER, TmgAction::DisableAutopilot);
+    assert!(!res.main_failed());
+    enable_autopilot(&tamagotchi);
+    let next_block = next_summary(&tamagotchi).expect("The summary isn't scheduled");
+    assert_ne!(next_block, first_block);
+
+    let results = sys.spend_blocks(BLOCKS_PER_DAY as u32 / 2 + 1);
+    assert!(!results.iter().any(|res| res.contains(&summary_log())));
+    assert_eq!(next_summary(&tamagotchi), Some(next_block));
+
+    let results = sys.spend_blocks(BLOCKS_PER_DAY as u32 / 2);
+    assert!(results.iter().any(|res| res.contains(&summary_log())));
+}
+
+#[test]
+fn budget_starts_over_without_the_summary() {
+    let sys = System::new();
+    sys.init_logger();
+    let tamagotchi = init_tamagotchi(&sys);
+
+    // without reservations no summary is ever sent, pokes run the autopilot
+    let res = tamagotchi.send(
+        OWNER,
+        TmgAction::EnableAutopilot {
+            thresholds: NeedThresholds {
+                fed: u64::MAX,
+                entertained: u64::MAX,
+                rested: u64::MAX,
+            },
+            daily_budget: 1,
+        },
+    );
+    assert!(!res.main_failed());
+    assert_eq!(next_summary(&tamagotchi), None);
+
+    let poke = || {
+        sys.spend_blocks(DELAY_OF_ONE_MINUTE);
+        let res = tamagotchi.send(USER, TmgAction::Poke);
+        assert!(!res.main_failed());
+        tamagotchi_state(&tamagotchi)
+            .autopilot
+            .expect("The autopilot isn't enabled")
+            .summary
+    };
+
+    // the budget allows a single feed, the other needs are skipped
+    let summary = poke();
+    assert_eq!((summary.feeds, summary.skipped), (1, 2));
+    let summary = poke();
+    assert_eq!((summary.feeds, summary.skipped), (1, 5));
+
+    sys.spend_blocks(BLOCKS_PER_DAY as u32);
+    let summary = poke();
+    assert_eq!((summary.feeds, summary.skipped), (2, 7));
+}
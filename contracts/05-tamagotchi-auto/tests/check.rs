@@ -0,0 +1,97 @@
+mod common;
+
+use common::{init_tamagotchi, reserve_gas, tamagotchi_state, OWNER, RESERVATION_DURATION, USER};
+use gtest::{Log, Program, System};
+use tamagotchi_auto_io::{Need, NeedThresholds, Scheduler, TmgAction, TmgEvent};
+
+const CHECK_INTERVAL: u32 = 5;
+
+fn set_warning_thresholds(tamagotchi: &Program<'_>, thresholds: NeedThresholds) {
+    let res = tamagotchi.send(OWNER, TmgAction::SetWarningThresholds(thresholds));
+    let log = Log::builder()
+        .dest(OWNER)
+        .payload(TmgEvent::WarningThresholdsSet);
+    assert!(res.contains(&log));
+}
+
+#[test]
+fn check_reports_every_need_under_its_threshold() {
+    let sys = System::new();
+    sys.init_logger();
+    let tamagotchi = init_tamagotchi(&sys);
+
+    let res = tamagotchi.send(USER, TmgAction::CheckState);
+    let log = Log::builder().dest(USER).payload(TmgEvent::AllGood);
+    assert!(res.contains(&log));
+
+    // the needs start at 5000, the feed and sleep ones are reported
+    set_warning_thresholds(
+        &tamagotchi,
+        NeedThresholds {
+            fed: 6_000,
+            entertained: 1_000,
+            rested: 6_000,
+        },
+    );
+    let res = tamagotchi.send(USER, TmgAction::CheckState);
+    let log = Log::builder()
+        .dest(USER)
+        .payload(TmgEvent::Needs(vec![Need::Feed, Need::Sleep]));
+    assert!(res.contains(&log));
+
+    // only the owner sets the thresholds
+    let res = tamagotchi.send(
+        USER,
+        TmgAction::SetWarningThresholds(NeedThresholds::default()),
+    );
+    assert!(!res.main_failed());
+    let state = tamagotchi_state(&tamagotchi);
+    assert_eq!(state.warning_thresholds.fed, 6_000);
+}
+
+#[test]
+fn checks_run_at_the_configured_interval() {
+    let sys = System::new();
+    sys.init_logger();
+    let tamagotchi = init_tamagotchi(&sys);
+
+    let res = tamagotchi.send(OWNER, TmgAction::SetCheckInterval(0));
+    assert!(res.main_failed());
+
+    let res = tamagotchi.send(USER, TmgAction::SetCheckInterval(CHECK_INTERVAL));
+    assert!(!res.main_failed());
+    let log = Log::builder()
+        .dest(USER)
+        .payload(TmgEvent::CheckIntervalSet(CHECK_INTERVAL));
+    assert!(!res.contains(&log));
+
+    let res = tamagotchi.send(OWNER, TmgAction::SetCheckInterval(CHECK_INTERVAL));
+    let log = Log::builder()
+        .dest(OWNER)
+        .payload(TmgEvent::CheckIntervalSet(CHECK_INTERVAL));
+    assert!(res.contains(&log));
+
+    set_warning_thresholds(
+        &tamagotchi,
+        NeedThresholds {
+            fed: 10_000,
+            entertained: 10_000,
+            rested: 10_000,
+        },
+    );
+    for _ in 0..3 {
+        reserve_gas(&tamagotchi, RESERVATION_DURATION);
+    }
+    let state = tamagotchi_state(&tamagotchi);
+    assert!(matches!(state.scheduler, Scheduler::Scheduled { .. }));
+    assert_eq!(state.check_interval, CHECK_INTERVAL);
+
+    // the owner gets every need in one message
+    let results = sys.spend_blocks(CHECK_INTERVAL + 1);
+    let log = Log::builder().dest(OWNER).payload(TmgEvent::Needs(vec![
+        Need::Feed,
+        Need::Play,
+        Need::Sleep,
+    ]));
+    assert!(results.iter().any(|res| res.contains(&log)));
+}
@@ -0,0 +1,86 @@
+#![allow(dead_code)]
+
+use gstd::{codec::Decode, ActorId};
+use gtest::{Log, Program, System};
+use sharded_fungible_token_io::InitFToken;
+use tamagotchi_auto_io::{GasReservation, Tamagotchi, TmgAction, TmgEvent, TmgInit};
+
+pub const OWNER: u64 = 100;
+pub const USER: u64 = 101;
+pub const RESERVATION_AMOUNT: u64 = 1_000_000_000;
+pub const RESERVATION_DURATION: u32 = 1_000;
+
+const FT_STORAGE_WASM: &str =
+    "../target/wasm32-unknown-unknown/release/sharded_fungible_token_storage.opt.wasm";
+const FT_LOGIC_WASM: &str =
+    "../target/wasm32-unknown-unknown/release/sharded_fungible_token_logic.opt.wasm";
+const FT_MAIN_WASM: &str =
+    "../target/wasm32-unknown-unknown/release/sharded_fungible_token.opt.wasm";
+
+pub fn actor_id(program: &Program<'_>) -> ActorId {
+    ActorId::new(program.id().into_bytes())
+}
+
+pub fn init_ft(sys: &System) -> Program<'_> {
+    let storage_code_id = sys.submit_code(FT_STORAGE_WASM);
+    let logic_code_id = sys.submit_code(FT_LOGIC_WASM);
+    let ft = Program::from_file(sys, FT_MAIN_WASM);
+    let res = ft.send(
+        OWNER,
+        InitFToken {
+            storage_code_hash: storage_code_id.into_bytes().into(),
+            ft_logic_code_hash: logic_code_id.into_bytes().into(),
+        },
+    );
+    assert!(!res.main_failed());
+
+    ft
+}
+
+pub fn init_tamagotchi(sys: &System) -> Program<'_> {
+    let tamagotchi = Program::current(sys);
+    let res = tamagotchi.send(
+        OWNER,
+        TmgInit {
+            owner: OWNER.into(),
+            name: String::from("Tama"),
+            factory: None,
+            imported: None,
+        },
+    );
+    assert!(!res.main_failed());
+
+    tamagotchi
+}
+
+/// Reserves [`RESERVATION_AMOUNT`] of gas for `duration` blocks on behalf of
+/// the owner.
+pub fn reserve_gas(tamagotchi: &Program<'_>, duration: u32) {
+    let res = tamagotchi.send(
+        OWNER,
+        TmgAction::ReserveGas {
+            reservation_amount: RESERVATION_AMOUNT,
+            duration,
+        },
+    );
+    let log = Log::builder().dest(OWNER).payload(TmgEvent::GasReserved);
+    assert!(res.contains(&log));
+}
+
+/// The reservations the Tamagotchi can still use.
+pub fn reservations(tamagotchi: &Program<'_>) -> Vec<GasReservation> {
+    let res = tamagotchi.send(USER, TmgAction::Reservations);
+    res.log()
+        .iter()
+        .find_map(|log| match TmgEvent::decode(&mut log.payload()) {
+            Ok(TmgEvent::Reservations(reservations)) => Some(reservations),
+            _ => None,
+        })
+        .expect("No `TmgEvent::Reservations` in the reply")
+}
+
+pub fn tamagotchi_state(tamagotchi: &Program<'_>) -> Tamagotchi {
+    tamagotchi
+        .read_state(b"")
+        .expect("Unable to read the state")
+}
@@ -0,0 +1,124 @@
+mod common;
+
+use common::{init_tamagotchi, OWNER, USER};
+use gtest::{Log, Program, System};
+use tamagotchi_auto_io::{TmgAction, TmgEvent, TmgInit, DELAY_OF_ONE_MINUTE};
+
+const FACTORY: u64 = 102;
+const KEEPER: u64 = 103;
+const REWARD: u128 = 1_000_000_000_000;
+
+fn init_with_factory(sys: &System) -> Program<'_> {
+    let tamagotchi = Program::current(sys);
+    let res = tamagotchi.send(
+        FACTORY,
+        TmgInit {
+            owner: OWNER.into(),
+            name: String::from("Tama"),
+            factory: Some(FACTORY.into()),
+            imported: None,
+        },
+    );
+    assert!(!res.main_failed());
+
+    tamagotchi
+}
+
+fn delegated(caller: u64, action: TmgAction) -> TmgAction {
+    TmgAction::Delegated {
+        caller: caller.into(),
+        action: Box::new(action),
+    }
+}
+
+#[test]
+fn only_the_creator_can_be_the_factory() {
+    let sys = System::new();
+    sys.init_logger();
+
+    let tamagotchi = Program::current(&sys);
+    let res = tamagotchi.send(
+        OWNER,
+        TmgInit {
+            owner: OWNER.into(),
+            name: String::from("Tama"),
+            factory: Some(FACTORY.into()),
+            imported: None,
+        },
+    );
+    assert!(res.main_failed());
+
+    // a program creating a Tamagotchi for another owner isn't trusted
+    // unless it sets itself as the factory
+    let tamagotchi = Program::current(&sys);
+    let res = tamagotchi.send(
+        FACTORY,
+        TmgInit {
+            owner: OWNER.into(),
+            name: String::from("Tama"),
+            factory: None,
+            imported: None,
+        },
+    );
+    assert!(!res.main_failed());
+    let res = tamagotchi.send(FACTORY, delegated(OWNER, TmgAction::Feed));
+    assert!(res.main_failed());
+}
+
+#[test]
+fn factory_forwards_the_rights_of_its_callers() {
+    let sys = System::new();
+    sys.init_logger();
+    let tamagotchi = init_with_factory(&sys);
+
+    let res = tamagotchi.send(FACTORY, delegated(OWNER, TmgAction::Approve(USER.into())));
+    let log = Log::builder()
+        .dest(FACTORY)
+        .payload(TmgEvent::Approved(USER.into()));
+    assert!(res.contains(&log));
+
+    // the factory itself has no rights on the Tamagotchi
+    let res = tamagotchi.send(FACTORY, delegated(KEEPER, TmgAction::RevokeApproval));
+    assert!(!res.contains(
+        &Log::builder()
+            .dest(FACTORY)
+            .payload(TmgEvent::ApprovalRevoked)
+    ));
+
+    // only the factory forwards actions
+    let res = tamagotchi.send(USER, delegated(OWNER, TmgAction::RevokeApproval));
+    assert!(res.main_failed());
+
+    // a Tamagotchi created by its owner has no factory
+    let tamagotchi = init_tamagotchi(&sys);
+    let res = tamagotchi.send(FACTORY, delegated(OWNER, TmgAction::Feed));
+    assert!(res.main_failed());
+}
+
+#[test]
+fn forwarded_poke_pays_the_keeper() {
+    let sys = System::new();
+    sys.init_logger();
+    let tamagotchi = init_with_factory(&sys);
+
+    sys.mint_to(OWNER, REWARD * 2);
+    let res = tamagotchi.send_with_value(OWNER, TmgAction::FundKeeperPool { ft_amount: 0 }, REWARD);
+    assert!(!res.main_failed());
+    let res = tamagotchi.send(
+        OWNER,
+        TmgAction::SetKeeperReward {
+            native: REWARD,
+            ft: 0,
+        },
+    );
+    assert!(!res.main_failed());
+
+    sys.spend_blocks(DELAY_OF_ONE_MINUTE);
+    let res = tamagotchi.send(FACTORY, delegated(KEEPER, TmgAction::Poke));
+    let event = TmgEvent::Poked {
+        native_reward: REWARD,
+        ft_reward: 0,
+    };
+    let log = Log::builder().dest(KEEPER).payload(event);
+    assert!(res.contains(&log));
+}
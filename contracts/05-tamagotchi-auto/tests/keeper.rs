@@ -0,0 +1,83 @@
+mod common;
+
+use common::{init_tamagotchi, OWNER, USER as KEEPER};
+use gtest::{Log, Program, System};
+use tamagotchi_auto_io::{TmgAction, TmgEvent, DELAY_OF_ONE_MINUTE};
+
+const ONE_VARA: u128 = 1_000_000_000_000;
+const REWARD: u128 = ONE_VARA;
+
+fn init_keeper_pool(sys: &System) -> Program<'_> {
+    let tamagotchi = init_tamagotchi(sys);
+    sys.mint_to(OWNER, REWARD * 2 + ONE_VARA);
+    let res = tamagotchi.send_with_value(
+        OWNER,
+        TmgAction::FundKeeperPool { ft_amount: 0 },
+        REWARD * 2,
+    );
+    let log = Log::builder()
+        .dest(OWNER)
+        .payload(TmgEvent::KeeperPoolFunded {
+            native: REWARD * 2,
+            ft: 0,
+        });
+    assert!(res.contains(&log));
+
+    let res = tamagotchi.send(
+        OWNER,
+        TmgAction::SetKeeperReward {
+            native: REWARD,
+            ft: 0,
+        },
+    );
+    let log = Log::builder()
+        .dest(OWNER)
+        .payload(TmgEvent::KeeperRewardSet);
+    assert!(res.contains(&log));
+
+    tamagotchi
+}
+
+#[test]
+fn keeper_is_rewarded_once_per_interval() {
+    let sys = System::new();
+    sys.init_logger();
+    let tamagotchi = init_keeper_pool(&sys);
+
+    // the check isn't due yet
+    let res = tamagotchi.send(KEEPER, TmgAction::Poke);
+    assert!(res.main_failed());
+
+    sys.spend_blocks(DELAY_OF_ONE_MINUTE);
+    let res = tamagotchi.send(KEEPER, TmgAction::Poke);
+    let log = Log::builder().dest(KEEPER).payload(TmgEvent::Poked {
+        native_reward: REWARD,
+        ft_reward: 0,
+    });
+    assert!(res.contains(&log));
+
+    // a second poke in the same interval isn't rewarded
+    let res = tamagotchi.send(KEEPER, TmgAction::Poke);
+    assert!(res.main_failed());
+}
+
+#[test]
+fn only_owner_funds_keeper_pool() {
+    let sys = System::new();
+    sys.init_logger();
+    let tamagotchi = init_keeper_pool(&sys);
+
+    sys.mint_to(KEEPER, REWARD + ONE_VARA);
+    let res =
+        tamagotchi.send_with_value(KEEPER, TmgAction::FundKeeperPool { ft_amount: 0 }, REWARD);
+    assert!(res.main_failed());
+
+    let res = tamagotchi.send(
+        KEEPER,
+        TmgAction::SetKeeperReward {
+            native: REWARD * 2,
+            ft: 0,
+        },
+    );
+    assert!(res.log().is_empty());
+}
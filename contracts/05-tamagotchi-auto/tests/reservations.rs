@@ -0,0 +1,52 @@
+mod common;
+
+use common::{
+    init_tamagotchi, reservations, reserve_gas, OWNER, RESERVATION_AMOUNT, RESERVATION_DURATION,
+    USER,
+};
+use gtest::{Log, System};
+use tamagotchi_auto_io::{TmgAction, TmgEvent};
+
+#[test]
+fn expired_reservations_are_dropped() {
+    let sys = System::new();
+    sys.init_logger();
+    let tamagotchi = init_tamagotchi(&sys);
+
+    reserve_gas(&tamagotchi, 10);
+    reserve_gas(&tamagotchi, RESERVATION_DURATION);
+    let listed = reservations(&tamagotchi);
+    assert_eq!(listed.len(), 2);
+    assert!(listed
+        .iter()
+        .all(|reservation| reservation.amount == RESERVATION_AMOUNT));
+
+    sys.spend_blocks(10);
+    let active = reservations(&tamagotchi);
+    assert_eq!(active, listed[1..]);
+
+    // an expired reservation can't be unreserved anymore
+    let res = tamagotchi.send(OWNER, TmgAction::Unreserve(listed[0].id));
+    assert!(res.main_failed());
+}
+
+#[test]
+fn owner_unreserves_gas() {
+    let sys = System::new();
+    sys.init_logger();
+    let tamagotchi = init_tamagotchi(&sys);
+    reserve_gas(&tamagotchi, RESERVATION_DURATION);
+    let id = reservations(&tamagotchi)[0].id;
+
+    // only the owner gets the gas back
+    let res = tamagotchi.send(USER, TmgAction::Unreserve(id));
+    assert!(!res.main_failed());
+    assert_eq!(reservations(&tamagotchi).len(), 1);
+
+    let res = tamagotchi.send(OWNER, TmgAction::Unreserve(id));
+    let log = Log::builder().dest(OWNER).payload(TmgEvent::Unreserved {
+        amount: RESERVATION_AMOUNT,
+    });
+    assert!(res.contains(&log));
+    assert!(reservations(&tamagotchi).is_empty());
+}
@@ -0,0 +1,130 @@
+mod common;
+
+use common::{init_tamagotchi, tamagotchi_state, OWNER, RESERVATION_DURATION, USER};
+use gtest::{Log, Program, System};
+use tamagotchi_auto_io::{Scheduler, TmgAction, TmgEvent, DELAY_OF_ONE_MINUTE};
+
+fn reserve_gas(tamagotchi: &Program<'_>) {
+    common::reserve_gas(tamagotchi, RESERVATION_DURATION);
+}
+
+fn scheduler(tamagotchi: &Program<'_>) -> Scheduler {
+    tamagotchi_state(tamagotchi).scheduler
+}
+
+#[test]
+fn scheduler_starts_with_enough_reservations() {
+    let sys = System::new();
+    sys.init_logger();
+    let tamagotchi = init_tamagotchi(&sys);
+    assert_eq!(scheduler(&tamagotchi), Scheduler::Idle);
+
+    reserve_gas(&tamagotchi);
+    reserve_gas(&tamagotchi);
+    assert_eq!(scheduler(&tamagotchi), Scheduler::Idle);
+
+    reserve_gas(&tamagotchi);
+    let Scheduler::Scheduled {
+        message_id,
+        next_block,
+    } = scheduler(&tamagotchi)
+    else {
+        panic!("The scheduler wasn't started");
+    };
+
+    // a running loop can't be started twice
+    let res = tamagotchi.send(OWNER, TmgAction::RestartScheduler);
+    assert!(res.main_failed());
+
+    // a check from another account doesn't touch the loop
+    let res = tamagotchi.send(USER, TmgAction::CheckState);
+    assert!(!res.main_failed());
+    assert_eq!(
+        scheduler(&tamagotchi),
+        Scheduler::Scheduled {
+            message_id,
+            next_block
+        }
+    );
+}
+
+#[test]
+fn each_check_is_a_new_message() {
+    let sys = System::new();
+    sys.init_logger();
+    let tamagotchi = init_tamagotchi(&sys);
+    for _ in 0..4 {
+        reserve_gas(&tamagotchi);
+    }
+    let Scheduler::Scheduled { message_id, .. } = scheduler(&tamagotchi) else {
+        panic!("The scheduler wasn't started");
+    };
+
+    // the scheduled check goes on with a new one
+    sys.spend_blocks(DELAY_OF_ONE_MINUTE + 1);
+    let Scheduler::Scheduled {
+        message_id: next_message_id,
+        ..
+    } = scheduler(&tamagotchi)
+    else {
+        panic!("The loop stopped");
+    };
+    assert_ne!(next_message_id, message_id);
+}
+
+#[test]
+fn scheduler_runs_out_of_gas() {
+    let sys = System::new();
+    sys.init_logger();
+    let tamagotchi = init_tamagotchi(&sys);
+    for _ in 0..3 {
+        reserve_gas(&tamagotchi);
+    }
+
+    // the first check uses one reservation and schedules the next one,
+    // the last reservation is kept to ask the owner for more gas
+    sys.spend_blocks(DELAY_OF_ONE_MINUTE + 1);
+    assert!(matches!(
+        scheduler(&tamagotchi),
+        Scheduler::Scheduled { .. }
+    ));
+
+    sys.spend_blocks(DELAY_OF_ONE_MINUTE);
+    assert_eq!(scheduler(&tamagotchi), Scheduler::OutOfGas);
+
+    let results = sys.spend_blocks(DELAY_OF_ONE_MINUTE);
+    let log = Log::builder()
+        .dest(OWNER)
+        .payload(TmgEvent::MakeReservation);
+    assert!(results.iter().any(|res| res.contains(&log)));
+
+    // nothing left to restart the loop with
+    let res = tamagotchi.send(OWNER, TmgAction::RestartScheduler);
+    assert!(res.main_failed());
+    assert_eq!(scheduler(&tamagotchi), Scheduler::OutOfGas);
+}
+
+#[test]
+fn owner_restarts_scheduler() {
+    let sys = System::new();
+    sys.init_logger();
+    let tamagotchi = init_tamagotchi(&sys);
+
+    // a single reservation doesn't start the loop by itself
+    reserve_gas(&tamagotchi);
+    assert_eq!(scheduler(&tamagotchi), Scheduler::Idle);
+
+    // only the owner can restart the loop
+    let res = tamagotchi.send(USER, TmgAction::RestartScheduler);
+    assert!(!res.main_failed());
+    assert_eq!(scheduler(&tamagotchi), Scheduler::Idle);
+
+    let res = tamagotchi.send(OWNER, TmgAction::RestartScheduler);
+    let Scheduler::Scheduled { next_block, .. } = scheduler(&tamagotchi) else {
+        panic!("The scheduler wasn't restarted");
+    };
+    let log = Log::builder()
+        .dest(OWNER)
+        .payload(TmgEvent::SchedulerRestarted { next_block });
+    assert!(res.contains(&log));
+}
@@ -0,0 +1,120 @@
+mod common;
+
+use common::{actor_id, init_ft, init_tamagotchi, tamagotchi_state, OWNER, USER as SPENDER};
+use gstd::ActorId;
+use gtest::{Log, Program, System};
+use tamagotchi_auto_io::{TmgAction, TmgEvent, TmgInit};
+
+const BROKEN_FT: u64 = 2;
+const AMOUNT: u128 = 1_000;
+
+// The fungible token contract is another Tamagotchi, it can't decode
+// `FTokenAction`, so every approval fails while waiting for the reply.
+fn init_with_broken_ft(sys: &System) -> Program<'_> {
+    let tamagotchi = Program::current(sys);
+    let broken_ft = Program::current_with_id(sys, BROKEN_FT);
+    for program in [&tamagotchi, &broken_ft] {
+        let res = program.send(
+            OWNER,
+            TmgInit {
+                owner: OWNER.into(),
+                name: String::from("Tama"),
+                factory: None,
+                imported: None,
+            },
+        );
+        assert!(!res.main_failed());
+    }
+
+    set_ft_contract(&tamagotchi, BROKEN_FT.into());
+
+    tamagotchi
+}
+
+fn set_ft_contract(tamagotchi: &Program<'_>, ft_contract_id: ActorId) {
+    let res = tamagotchi.send(OWNER, TmgAction::SetFTokenContract(ft_contract_id));
+    let log = Log::builder()
+        .dest(OWNER)
+        .payload(TmgEvent::FTokenContractSet);
+    assert!(res.contains(&log));
+}
+
+fn approve_transaction(tamagotchi: &Program<'_>) -> Option<(u64, ActorId, u128)> {
+    tamagotchi_state(tamagotchi).approve_transaction
+}
+
+#[test]
+fn failed_approval_is_rolled_back() {
+    let sys = System::new();
+    sys.init_logger();
+    let tamagotchi = init_with_broken_ft(&sys);
+
+    let res = tamagotchi.send(
+        OWNER,
+        TmgAction::ApproveTokens {
+            account: SPENDER.into(),
+            amount: AMOUNT,
+        },
+    );
+    assert!(res.main_failed());
+    assert_eq!(approve_transaction(&tamagotchi), None);
+
+    // another approval isn't blocked by the failed one
+    let res = tamagotchi.send(
+        OWNER,
+        TmgAction::ApproveTokens {
+            account: OWNER.into(),
+            amount: AMOUNT * 2,
+        },
+    );
+    assert!(!res.contains(&Log::builder().dest(OWNER).payload(TmgEvent::ApprovalError)));
+}
+
+#[test]
+fn approval_out_of_gas_is_rolled_back() {
+    let sys = System::new();
+    sys.init_logger();
+    let ft = init_ft(&sys);
+    let tamagotchi = init_tamagotchi(&sys);
+    set_ft_contract(&tamagotchi, actor_id(&ft));
+
+    // whether the gas runs out before sending the approval or while waiting
+    // for the reply, no approval is left pending and the next approval of
+    // another amount goes through
+    let mut failed = 0;
+    let mut approved = 0;
+    for gas_limit in (1..=20).map(|i| i * 1_000_000_000) {
+        let res = tamagotchi.send_with_gas(
+            OWNER,
+            TmgAction::ApproveTokens {
+                account: SPENDER.into(),
+                amount: AMOUNT,
+            },
+            gas_limit,
+            0,
+        );
+        if res.main_failed() {
+            failed += 1;
+        } else {
+            approved += 1;
+        }
+        assert_eq!(approve_transaction(&tamagotchi), None);
+
+        let res = tamagotchi.send(
+            OWNER,
+            TmgAction::ApproveTokens {
+                account: SPENDER.into(),
+                amount: AMOUNT * 2,
+            },
+        );
+        let log = Log::builder()
+            .dest(OWNER)
+            .payload(TmgEvent::TokensApproved {
+                account: SPENDER.into(),
+                amount: AMOUNT * 2,
+            });
+        assert!(res.contains(&log));
+    }
+    assert!(failed > 0, "The gas never ran out");
+    assert!(approved > 0, "The approval never succeeded");
+}
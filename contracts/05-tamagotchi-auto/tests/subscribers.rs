@@ -0,0 +1,107 @@
+mod common;
+
+use common::{init_tamagotchi, reserve_gas, OWNER, RESERVATION_DURATION, USER};
+use gtest::{Log, Program, System};
+use tamagotchi_auto_io::{NeedFilter, NeedThresholds, TmgAction, TmgEvent, DELAY_OF_ONE_MINUTE};
+
+const CARETAKER: u64 = 102;
+const BOT: u64 = 103;
+
+fn subscribe(tamagotchi: &Program<'_>, from: u64, subscriber: u64, filter: NeedFilter) {
+    let res = tamagotchi.send(
+        from,
+        TmgAction::Subscribe {
+            subscriber: subscriber.into(),
+            filter,
+        },
+    );
+    let log = Log::builder()
+        .dest(from)
+        .payload(TmgEvent::Subscribed(subscriber.into()));
+    assert!(res.contains(&log));
+}
+
+#[test]
+fn subscribers_get_filtered_needs() {
+    let sys = System::new();
+    sys.init_logger();
+    let tamagotchi = init_tamagotchi(&sys);
+
+    let feed_only = NeedFilter {
+        feed: true,
+        ..NeedFilter::default()
+    };
+    subscribe(&tamagotchi, OWNER, CARETAKER, feed_only);
+    subscribe(&tamagotchi, OWNER, BOT, NeedFilter::default());
+
+    // every need is under the threshold right away
+    let res = tamagotchi.send(
+        OWNER,
+        TmgAction::SetWarningThresholds(NeedThresholds {
+            fed: 10_000,
+            entertained: 10_000,
+            rested: 10_000,
+        }),
+    );
+    assert!(!res.main_failed());
+    for _ in 0..3 {
+        reserve_gas(&tamagotchi, RESERVATION_DURATION);
+    }
+
+    let results = sys.spend_blocks(DELAY_OF_ONE_MINUTE + 1);
+    let received = |subscriber: u64, event: TmgEvent| {
+        let log = Log::builder().dest(subscriber).payload(event);
+        results.iter().any(|res| res.contains(&log))
+    };
+    assert!(received(CARETAKER, TmgEvent::FeedMe));
+    assert!(!received(CARETAKER, TmgEvent::PlayWithMe));
+    assert!(!received(CARETAKER, TmgEvent::WantToSleep));
+    assert!(!received(BOT, TmgEvent::FeedMe));
+}
+
+#[test]
+fn subscriber_limit() {
+    let sys = System::new();
+    sys.init_logger();
+    let tamagotchi = init_tamagotchi(&sys);
+
+    // only the owner or an approved account manages the subscribers
+    let res = tamagotchi.send(
+        USER,
+        TmgAction::Subscribe {
+            subscriber: USER.into(),
+            filter: NeedFilter::default(),
+        },
+    );
+    let log = Log::builder()
+        .dest(USER)
+        .payload(TmgEvent::Subscribed(USER.into()));
+    assert!(!res.contains(&log));
+
+    let res = tamagotchi.send(OWNER, TmgAction::SetMaxSubscribers(1));
+    let log = Log::builder()
+        .dest(OWNER)
+        .payload(TmgEvent::MaxSubscribersSet(1));
+    assert!(res.contains(&log));
+
+    subscribe(&tamagotchi, OWNER, CARETAKER, NeedFilter::default());
+    // the filter of a subscriber can still be changed at the limit
+    subscribe(&tamagotchi, OWNER, CARETAKER, NeedFilter::default());
+
+    let res = tamagotchi.send(
+        OWNER,
+        TmgAction::Subscribe {
+            subscriber: BOT.into(),
+            filter: NeedFilter::default(),
+        },
+    );
+    assert!(res.main_failed());
+
+    // a subscriber can leave by itself
+    let res = tamagotchi.send(CARETAKER, TmgAction::Unsubscribe(CARETAKER.into()));
+    let log = Log::builder()
+        .dest(CARETAKER)
+        .payload(TmgEvent::Unsubscribed(CARETAKER.into()));
+    assert!(res.contains(&log));
+    subscribe(&tamagotchi, OWNER, BOT, NeedFilter::default());
+}
@@ -1,13 +1,40 @@
 #![no_std]
 
+use futures::future::join_all;
 use gmeta::{In, InOut, Metadata, Out};
-use gstd::{collections::BTreeMap, msg, prelude::*, prog::ProgramGenerator, ActorId, CodeId};
+use gstd::{
+    collections::{BTreeMap, BTreeSet},
+    exec, msg,
+    prelude::*,
+    prog::ProgramGenerator,
+    ActorId, CodeId,
+};
 use tamagotchi_auto_io::*;
 
 pub type TamagotchiId = u64;
 pub type AttributeId = u32;
 
-const GAS_FOR_CREATION: u64 = 5_000_000_000; // 1_000_000_000;
+pub const GAS_FOR_CREATION: u64 = 5_000_000_000; // 1_000_000_000;
+/// Gas given to every message of a batch, the batch size is capped by the
+/// gas attached to it.
+pub const GAS_FOR_BATCH_OP: u64 = 2_000_000_000;
+/// Gas a batch keeps to collect the results and reply.
+pub const GAS_FOR_BATCH_REPLY: u64 = 1_000_000_000;
+
+/// A result per operation of a batch.
+pub type BatchResult = Vec<(TamagotchiId, BatchOpResult)>;
+
+#[derive(Encode, Decode, TypeInfo, PartialEq, Eq)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub enum BatchOpResult {
+    Done(TmgEvent),
+    UnknownTamagotchi,
+    /// The Tamagotchi failed to handle the operation.
+    Failed,
+    /// The gas left wasn't enough to send the operation.
+    NotSent,
+}
 
 pub struct ProgramMetadata;
 
@@ -27,6 +54,18 @@ pub struct TamagotchiFactory {
     pub tamagotchi_number: TamagotchiId,
     pub id_to_address: BTreeMap<TamagotchiId, ActorId>,
     pub tamagotchi_code_id: CodeId,
+    /// The Tamagotchis of every owner. It follows the transfers made through
+    /// the factory, a Tamagotchi transferred on its own program stays under
+    /// its previous owner until it's transferred through the factory again.
+    pub owner_to_ids: BTreeMap<ActorId, BTreeSet<TamagotchiId>>,
+    pub address_to_id: BTreeMap<ActorId, TamagotchiId>,
+    pub admin: ActorId,
+    /// Version of `tamagotchi_code_id`.
+    pub tamagotchi_version: u32,
+    /// The code version each Tamagotchi runs.
+    pub versions: BTreeMap<TamagotchiId, u32>,
+    /// The owner each Tamagotchi is indexed under in `owner_to_ids`.
+    pub id_to_owner: BTreeMap<TamagotchiId, ActorId>,
 }
 
 impl TamagotchiFactory {
@@ -36,6 +75,8 @@ impl TamagotchiFactory {
             TmgInit {
                 owner: *tamagotchi_owner,
                 name: tamagotchi_name,
+                factory: Some(exec::program_id()),
+                imported: None,
             },
             GAS_FOR_CREATION,
             0,
@@ -46,6 +87,15 @@ impl TamagotchiFactory {
         .expect("Program was not initialized");
         self.tamagotchi_number = self.tamagotchi_number.saturating_add(1);
         self.id_to_address.insert(self.tamagotchi_number, address);
+        self.address_to_id.insert(address, self.tamagotchi_number);
+        self.versions
+            .insert(self.tamagotchi_number, self.tamagotchi_version);
+        self.owner_to_ids
+            .entry(*tamagotchi_owner)
+            .or_default()
+            .insert(self.tamagotchi_number);
+        self.id_to_owner
+            .insert(self.tamagotchi_number, *tamagotchi_owner);
         msg::reply(
             TamagotchiFactoryEvent::TamagotchiCreated {
                 tamagotchi_id: self.tamagotchi_number,
@@ -56,6 +106,73 @@ impl TamagotchiFactory {
         .expect("Error during a reply `FactoryEvent::ProgramCreated`");
     }
 
+    pub fn set_tamagotchi_code(&mut self, code_id: CodeId, version: u32) {
+        assert_eq!(msg::source(), self.admin, "Only the admin can set the code");
+        assert!(
+            version > self.tamagotchi_version,
+            "The version must be newer than the current one"
+        );
+        self.tamagotchi_code_id = code_id;
+        self.tamagotchi_version = version;
+        msg::reply(
+            TamagotchiFactoryEvent::TamagotchiCodeSet { code_id, version },
+            0,
+        )
+        .expect("Error sending reply");
+    }
+
+    /// Moves the Tamagotchi to a program from the current code. The old
+    /// program exports its state, and its keeper pool value, which the new
+    /// program is created with.
+    pub async fn migrate(&mut self, tamagotchi_id: TamagotchiId) {
+        let version = self
+            .versions
+            .get(&tamagotchi_id)
+            .copied()
+            .unwrap_or_default();
+        assert!(
+            version < self.tamagotchi_version,
+            "The Tamagotchi already runs the current code"
+        );
+
+        let old_address = self.get_tamagotchi_address(tamagotchi_id);
+        let TmgEvent::Exported(state) = Self::send_message(&old_address, TmgAction::Export).await
+        else {
+            panic!("Incorrect answer from tamagotchi contract");
+        };
+
+        let value = state.keeper_pool.native;
+        let (address, _) = ProgramGenerator::create_program_with_gas_for_reply(
+            self.tamagotchi_code_id,
+            TmgInit {
+                owner: state.owner,
+                name: state.name.clone(),
+                factory: Some(exec::program_id()),
+                imported: Some(state),
+            },
+            GAS_FOR_CREATION,
+            value,
+            5_000_000_000,
+        )
+        .expect("Error during Tamagotchi program initialization")
+        .await
+        .expect("Program was not initialized");
+
+        self.id_to_address.insert(tamagotchi_id, address);
+        self.address_to_id.remove(&old_address);
+        self.address_to_id.insert(address, tamagotchi_id);
+        self.versions.insert(tamagotchi_id, self.tamagotchi_version);
+        msg::reply(
+            TamagotchiFactoryEvent::Migrated {
+                tamagotchi_id,
+                tamagotchi_address: address,
+                version: self.tamagotchi_version,
+            },
+            0,
+        )
+        .expect("Error sending reply");
+    }
+
     pub async fn get_tamagotchi_name(&self, tamagotchi_id: TamagotchiId) {
         let tamagotchi_address = self.get_tamagotchi_address(tamagotchi_id);
         let tamagotchi_name_ans = Self::send_message(&tamagotchi_address, TmgAction::Name).await;
@@ -111,15 +228,28 @@ impl TamagotchiFactory {
         msg::reply(TamagotchiFactoryEvent::Slept, 0).expect("Error sending reply");
     }
 
-    pub async fn transfer_tamagotchi(&self, tamagotchi_id: TamagotchiId, new_owner: ActorId) {
+    pub async fn transfer_tamagotchi(&mut self, tamagotchi_id: TamagotchiId, new_owner: ActorId) {
         let tamagotchi_address = self.get_tamagotchi_address(tamagotchi_id);
         let tamagotchi_ans =
             Self::send_message(&tamagotchi_address, TmgAction::Transfer(new_owner)).await;
 
-        if let TmgEvent::Transferred(_new_owner) = tamagotchi_ans {
+        if tamagotchi_ans != TmgEvent::Transferred(new_owner) {
             panic!("Incorrect answer from tamagotchi contract");
         }
 
+        if let Some(prev_owner) = self.id_to_owner.insert(tamagotchi_id, new_owner) {
+            if let Some(ids) = self.owner_to_ids.get_mut(&prev_owner) {
+                ids.remove(&tamagotchi_id);
+                if ids.is_empty() {
+                    self.owner_to_ids.remove(&prev_owner);
+                }
+            }
+        }
+        self.owner_to_ids
+            .entry(new_owner)
+            .or_default()
+            .insert(tamagotchi_id);
+
         msg::reply(TamagotchiFactoryEvent::Transferred(new_owner), 0).expect("Error sending reply");
     }
 
@@ -241,9 +371,7 @@ impl TamagotchiFactory {
         let tamagotchi_ans = Self::send_message(&tamagotchi_address, TmgAction::CheckState).await;
 
         let response = match tamagotchi_ans {
-            TmgEvent::FeedMe => TamagotchiFactoryEvent::FeedMe,
-            TmgEvent::PlayWithMe => TamagotchiFactoryEvent::PlayWithMe,
-            TmgEvent::WantToSleep => TamagotchiFactoryEvent::WantToSleep,
+            TmgEvent::Needs(needs) => TamagotchiFactoryEvent::Needs(needs),
             TmgEvent::AllGood => TamagotchiFactoryEvent::AllGood,
             _ => {
                 panic!("Incorrect answer from tamagotchi contract");
@@ -276,6 +404,88 @@ impl TamagotchiFactory {
         msg::reply(TamagotchiFactoryEvent::GasReserved, 0).expect("Error sending reply");
     }
 
+    /// Sends the operations to their Tamagotchis at once on behalf of the
+    /// caller and replies with the results. The operations the gas left
+    /// isn't enough for aren't sent.
+    pub async fn batch(&self, ops: Vec<(TamagotchiId, FactoryOp)>) {
+        let caller = msg::source();
+        let mut results: BatchResult = Vec::with_capacity(ops.len());
+        let mut replies = Vec::new();
+        for (tamagotchi_id, op) in ops {
+            let result = if let Some(address) = self.id_to_address.get(&tamagotchi_id) {
+                if exec::gas_available() < GAS_FOR_BATCH_OP + GAS_FOR_BATCH_REPLY {
+                    BatchOpResult::NotSent
+                } else {
+                    let reply = msg::send_with_gas_for_reply_as::<_, TmgEvent>(
+                        *address,
+                        TmgAction::Delegated {
+                            caller,
+                            action: Box::new(op.action()),
+                        },
+                        GAS_FOR_BATCH_OP,
+                        0,
+                        0,
+                    )
+                    .expect("Error during a sending message to a Tamagotchi program");
+                    replies.push((results.len(), reply));
+                    // replaced by the reply once it arrives
+                    BatchOpResult::Failed
+                }
+            } else {
+                BatchOpResult::UnknownTamagotchi
+            };
+            results.push((tamagotchi_id, result));
+        }
+
+        let (indexes, replies): (Vec<_>, Vec<_>) = replies.into_iter().unzip();
+        for (index, reply) in indexes.into_iter().zip(join_all(replies).await) {
+            if let Ok(event) = reply {
+                results[index].1 = BatchOpResult::Done(event);
+            }
+        }
+        msg::reply(TamagotchiFactoryEvent::BatchResult(results), 0).expect("Error sending reply");
+    }
+
+    /// The same operation for every Tamagotchi of the caller.
+    pub async fn batch_all(&self, op: FactoryOp) {
+        let ops = self
+            .owner_to_ids
+            .get(&msg::source())
+            .into_iter()
+            .flatten()
+            .map(|tamagotchi_id| (*tamagotchi_id, op))
+            .collect();
+        self.batch(ops).await;
+    }
+
+    /// Ids of the Tamagotchis of `owner` in the order of creation, `limit`
+    /// of them starting at `offset`.
+    pub fn tamagotchis_of(&self, owner: ActorId, offset: u32, limit: u32) -> Vec<TamagotchiId> {
+        self.owner_to_ids
+            .get(&owner)
+            .into_iter()
+            .flatten()
+            .skip(offset as usize)
+            .take(limit as usize)
+            .copied()
+            .collect()
+    }
+
+    pub fn id_of(&self, address: ActorId) -> Option<TamagotchiId> {
+        self.address_to_id.get(&address).copied()
+    }
+
+    /// Ids and addresses of the Tamagotchis in the order of creation,
+    /// `limit` of them starting at `offset`.
+    pub fn all(&self, offset: u32, limit: u32) -> Vec<(TamagotchiId, ActorId)> {
+        self.id_to_address
+            .iter()
+            .skip(offset as usize)
+            .take(limit as usize)
+            .map(|(id, address)| (*id, *address))
+            .collect()
+    }
+
     pub fn get_tamagotchi_address(&self, tamagotchi_id: TamagotchiId) -> ActorId {
         *self
             .id_to_address
@@ -283,13 +493,18 @@ impl TamagotchiFactory {
             .expect("The escrow with indicated id does not exist")
     }
 
+    /// Forwards the action on behalf of the caller, so the Tamagotchi checks
+    /// the caller's rights instead of the factory's.
     pub async fn send_message(
         tamagotchi_address: &ActorId,
         tamagotchi_payload: TmgAction,
     ) -> TmgEvent {
         msg::send_for_reply_as::<_, TmgEvent>(
             *tamagotchi_address,
-            tamagotchi_payload,
+            TmgAction::Delegated {
+                caller: msg::source(),
+                action: Box::new(tamagotchi_payload),
+            },
             msg::value(),
             0,
         )
@@ -341,6 +556,56 @@ pub enum TamagotchiFactoryAction {
         reservation_amount: u64,
         duration: u32,
     },
+    TamagotchisOf {
+        owner: ActorId,
+        offset: u32,
+        limit: u32,
+    },
+    IdOf(ActorId),
+    All {
+        offset: u32,
+        limit: u32,
+    },
+    Batch(Vec<(TamagotchiId, FactoryOp)>),
+    FeedAll,
+    CheckAll,
+    SetTamagotchiCode {
+        code_id: CodeId,
+        version: u32,
+    },
+    Migrate(TamagotchiId),
+}
+
+#[derive(Encode, Decode, TypeInfo, Clone, Copy)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub enum FactoryOp {
+    Feed,
+    Play,
+    Sleep,
+    CheckState,
+    ReserveGas {
+        reservation_amount: u64,
+        duration: u32,
+    },
+}
+
+impl FactoryOp {
+    pub fn action(self) -> TmgAction {
+        match self {
+            FactoryOp::Feed => TmgAction::Feed,
+            FactoryOp::Play => TmgAction::Play,
+            FactoryOp::Sleep => TmgAction::Sleep,
+            FactoryOp::CheckState => TmgAction::CheckState,
+            FactoryOp::ReserveGas {
+                reservation_amount,
+                duration,
+            } => TmgAction::ReserveGas {
+                reservation_amount,
+                duration,
+            },
+        }
+    }
 }
 
 #[derive(Encode, Decode, TypeInfo)]
@@ -369,10 +634,25 @@ pub enum TamagotchiFactoryEvent {
     CompletePrevPurchase(AttributeId), //
     ErrorDuringPurchase,  //
     // TODO: 3️⃣ Add new events
+    // Replaced by `Needs`, kept so that the later variants keep their indices
     FeedMe,          //
     PlayWithMe,      //
     WantToSleep,     //
     AllGood,         // extra field to return if the user check state //
     MakeReservation, //
     GasReserved,
+    Needs(Vec<Need>),
+    TamagotchisOf(Vec<TamagotchiId>),
+    IdOf(Option<TamagotchiId>),
+    All(Vec<(TamagotchiId, ActorId)>),
+    BatchResult(BatchResult),
+    TamagotchiCodeSet {
+        code_id: CodeId,
+        version: u32,
+    },
+    Migrated {
+        tamagotchi_id: TamagotchiId,
+        tamagotchi_address: ActorId,
+        version: u32,
+    },
 }
@@ -11,6 +11,8 @@ extern fn init() {
         msg::load().expect("Unable to decode CodeId of the Escrow program");
     let tamagotchi_factory = TamagotchiFactory {
         tamagotchi_code_id,
+        admin: msg::source(),
+        tamagotchi_version: 1,
         ..Default::default()
     };
     unsafe { TAMAGOTCHI_FACTORY = Some(tamagotchi_factory) };
@@ -22,7 +24,7 @@ async fn main() {
     let factory = unsafe { TAMAGOTCHI_FACTORY.get_or_insert(Default::default()) };
     match action {
         TamagotchiFactoryAction::CreateTamagotchi { name } => {
-            factory.create_tamagotchi(&exec::program_id(), name).await;
+            factory.create_tamagotchi(&msg::source(), name).await;
         }
         TamagotchiFactoryAction::TamagotchiName(tamagotchi_id) => {
             factory.get_tamagotchi_name(tamagotchi_id).await;
@@ -92,6 +94,40 @@ async fn main() {
                 .reserve_gas_to_tamagotchi(tamagotchi_id, reservation_amount, duration)
                 .await;
         }
+        TamagotchiFactoryAction::TamagotchisOf {
+            owner,
+            offset,
+            limit,
+        } => {
+            msg::reply(
+                TamagotchiFactoryEvent::TamagotchisOf(factory.tamagotchis_of(owner, offset, limit)),
+                0,
+            )
+            .expect("Error sending reply");
+        }
+        TamagotchiFactoryAction::IdOf(address) => {
+            msg::reply(TamagotchiFactoryEvent::IdOf(factory.id_of(address)), 0)
+                .expect("Error sending reply");
+        }
+        TamagotchiFactoryAction::Batch(ops) => {
+            factory.batch(ops).await;
+        }
+        TamagotchiFactoryAction::FeedAll => {
+            factory.batch_all(FactoryOp::Feed).await;
+        }
+        TamagotchiFactoryAction::CheckAll => {
+            factory.batch_all(FactoryOp::CheckState).await;
+        }
+        TamagotchiFactoryAction::SetTamagotchiCode { code_id, version } => {
+            factory.set_tamagotchi_code(code_id, version);
+        }
+        TamagotchiFactoryAction::Migrate(tamagotchi_id) => {
+            factory.migrate(tamagotchi_id).await;
+        }
+        TamagotchiFactoryAction::All { offset, limit } => {
+            msg::reply(TamagotchiFactoryEvent::All(factory.all(offset, limit)), 0)
+                .expect("Error sending reply");
+        }
     }
 }
 
@@ -0,0 +1,31 @@
+#![no_std]
+use gmeta::{metawasm, Metadata};
+#[allow(unused_imports)]
+use gstd::{prelude::*, ActorId, Vec};
+use tamagotchi_army_io::{ProgramMetadata, TamagotchiId};
+
+#[metawasm]
+pub mod metafns {
+    pub type State = <ProgramMetadata as Metadata>::State;
+
+    /// Ids of the Tamagotchis of `owner` in the order of creation, `limit`
+    /// of them starting at `offset`.
+    pub fn tamagotchis_of(
+        state: State,
+        owner: ActorId,
+        offset: u32,
+        limit: u32,
+    ) -> Vec<TamagotchiId> {
+        state.tamagotchis_of(owner, offset, limit)
+    }
+
+    pub fn id_of(state: State, address: ActorId) -> Option<TamagotchiId> {
+        state.id_of(address)
+    }
+
+    /// Ids and addresses in the order of creation, `limit` of them starting
+    /// at `offset`.
+    pub fn all(state: State, offset: u32, limit: u32) -> Vec<(TamagotchiId, ActorId)> {
+        state.all(offset, limit)
+    }
+}
@@ -0,0 +1,111 @@
+mod common;
+
+use common::{create_tamagotchi, events, init_army, OWNER, USER};
+use gtest::{Program, RunResult, System};
+use tamagotchi_army_io::{
+    BatchOpResult, BatchResult, FactoryOp, TamagotchiFactoryAction, TamagotchiFactoryEvent,
+    GAS_FOR_BATCH_OP, GAS_FOR_BATCH_REPLY,
+};
+use tamagotchi_auto_io::TmgEvent;
+
+const UNKNOWN_ID: u64 = 999;
+const STRANGER: u64 = 200;
+
+fn batch_result(res: &RunResult, to: u64) -> BatchResult {
+    events(res, to)
+        .into_iter()
+        .find_map(|event| match event {
+            TamagotchiFactoryEvent::BatchResult(results) => Some(results),
+            _ => None,
+        })
+        .expect("No `TamagotchiFactoryEvent::BatchResult` in the reply")
+}
+
+fn create_tamagotchis(army: &Program<'_>, owner: u64, count: usize) -> Vec<u64> {
+    (0..count)
+        .map(|_| create_tamagotchi(army, owner).0)
+        .collect()
+}
+
+#[test]
+fn unknown_tamagotchi_fails_alone() {
+    let sys = System::new();
+    sys.init_logger();
+    let army = init_army(&sys);
+    let ids = create_tamagotchis(&army, OWNER, 2);
+
+    let res = army.send(
+        OWNER,
+        TamagotchiFactoryAction::Batch(vec![
+            (ids[0], FactoryOp::Feed),
+            (UNKNOWN_ID, FactoryOp::Feed),
+            (ids[1], FactoryOp::Play),
+        ]),
+    );
+    assert!(!res.main_failed());
+    let results = batch_result(&res, OWNER);
+    assert!(
+        results
+            == [
+                (ids[0], BatchOpResult::Done(TmgEvent::Fed)),
+                (UNKNOWN_ID, BatchOpResult::UnknownTamagotchi),
+                (ids[1], BatchOpResult::Done(TmgEvent::Entertained)),
+            ]
+    );
+}
+
+#[test]
+fn batch_is_capped_by_gas() {
+    let sys = System::new();
+    sys.init_logger();
+    let army = init_army(&sys);
+    let ids = create_tamagotchis(&army, OWNER, 10);
+
+    let ops = ids.iter().map(|id| (*id, FactoryOp::Feed)).collect();
+    let res = army.send_with_gas(
+        OWNER,
+        TamagotchiFactoryAction::Batch(ops),
+        4 * GAS_FOR_BATCH_OP + GAS_FOR_BATCH_REPLY,
+        0,
+    );
+    assert!(!res.main_failed());
+
+    // every operation gets a result, the first ones are sent
+    let results = batch_result(&res, OWNER);
+    assert_eq!(results.len(), ids.len());
+    assert!(results[0].1 == BatchOpResult::Done(TmgEvent::Fed));
+    assert!(results[ids.len() - 1].1 == BatchOpResult::NotSent);
+}
+
+#[test]
+fn feed_all_and_check_all_cover_the_callers_tamagotchis() {
+    let sys = System::new();
+    sys.init_logger();
+    let army = init_army(&sys);
+    let owner_ids = create_tamagotchis(&army, OWNER, 2);
+    create_tamagotchis(&army, USER, 1);
+
+    let res = army.send(OWNER, TamagotchiFactoryAction::FeedAll);
+    let results = batch_result(&res, OWNER);
+    assert!(
+        results
+            == owner_ids
+                .iter()
+                .map(|id| (*id, BatchOpResult::Done(TmgEvent::Fed)))
+                .collect::<Vec<_>>()
+    );
+
+    let res = army.send(OWNER, TamagotchiFactoryAction::CheckAll);
+    let results = batch_result(&res, OWNER);
+    assert!(
+        results
+            == owner_ids
+                .iter()
+                .map(|id| (*id, BatchOpResult::Done(TmgEvent::AllGood)))
+                .collect::<Vec<_>>()
+    );
+
+    // an account without Tamagotchis gets an empty result
+    let res = army.send(STRANGER, TamagotchiFactoryAction::CheckAll);
+    assert!(batch_result(&res, STRANGER).is_empty());
+}
@@ -0,0 +1,50 @@
+#![allow(dead_code)]
+
+use gstd::{codec::Decode, ActorId, CodeId};
+use gtest::{Program, RunResult, System};
+use tamagotchi_army_io::{TamagotchiFactoryAction, TamagotchiFactoryEvent, TamagotchiId};
+
+pub const ADMIN: u64 = 100;
+pub const OWNER: u64 = 101;
+pub const USER: u64 = 102;
+
+pub const TAMAGOTCHI_WASM: &str =
+    "../target/wasm32-unknown-unknown/release/tamagotchi_auto.opt.wasm";
+
+pub fn init_army(sys: &System) -> Program<'_> {
+    let code_id = sys.submit_code(TAMAGOTCHI_WASM);
+    let army = Program::current(sys);
+    let res = army.send(ADMIN, CodeId::from(code_id.into_bytes()));
+    assert!(!res.main_failed());
+
+    army
+}
+
+/// Every `TamagotchiFactoryEvent` the factory replied or sent to `to`.
+pub fn events(res: &RunResult, to: u64) -> Vec<TamagotchiFactoryEvent> {
+    res.log()
+        .iter()
+        .filter(|log| log.destination() == to.into())
+        .filter_map(|log| TamagotchiFactoryEvent::decode(&mut log.payload()).ok())
+        .collect()
+}
+
+/// Creates a Tamagotchi for `owner` and returns its id and address.
+pub fn create_tamagotchi(army: &Program<'_>, owner: u64) -> (TamagotchiId, ActorId) {
+    let res = army.send(
+        owner,
+        TamagotchiFactoryAction::CreateTamagotchi {
+            name: String::from("Tama"),
+        },
+    );
+    events(&res, owner)
+        .into_iter()
+        .find_map(|event| match event {
+            TamagotchiFactoryEvent::TamagotchiCreated {
+                tamagotchi_id,
+                tamagotchi_address,
+            } => Some((tamagotchi_id, tamagotchi_address)),
+            _ => None,
+        })
+        .expect("The Tamagotchi wasn't created")
+}
@@ -0,0 +1,96 @@
+mod common;
+
+use common::{create_tamagotchi, events, init_army, OWNER, USER};
+use gstd::ActorId;
+use gtest::{Log, Program, System};
+use tamagotchi_army_io::{
+    TamagotchiFactory, TamagotchiFactoryAction, TamagotchiFactoryEvent, TamagotchiId,
+};
+
+fn tamagotchis_of(army: &Program<'_>, owner: u64, offset: u32, limit: u32) -> Vec<TamagotchiId> {
+    let res = army.send(
+        USER,
+        TamagotchiFactoryAction::TamagotchisOf {
+            owner: owner.into(),
+            offset,
+            limit,
+        },
+    );
+    events(&res, USER)
+        .into_iter()
+        .find_map(|event| match event {
+            TamagotchiFactoryEvent::TamagotchisOf(ids) => Some(ids),
+            _ => None,
+        })
+        .expect("No `TamagotchiFactoryEvent::TamagotchisOf` in the reply")
+}
+
+#[test]
+fn queries_are_paginated() {
+    let sys = System::new();
+    sys.init_logger();
+    let army = init_army(&sys);
+
+    let (first_id, first_address) = create_tamagotchi(&army, OWNER);
+    let (user_id, _) = create_tamagotchi(&army, USER);
+    let (second_id, second_address) = create_tamagotchi(&army, OWNER);
+    let (third_id, _) = create_tamagotchi(&army, OWNER);
+
+    assert_eq!(
+        tamagotchis_of(&army, OWNER, 0, 10),
+        [first_id, second_id, third_id]
+    );
+    assert_eq!(tamagotchis_of(&army, OWNER, 1, 1), [second_id]);
+    assert!(tamagotchis_of(&army, OWNER, 3, 10).is_empty());
+    assert_eq!(tamagotchis_of(&army, USER, 0, 10), [user_id]);
+
+    let res = army.send(USER, TamagotchiFactoryAction::IdOf(first_address));
+    let log = Log::builder()
+        .dest(USER)
+        .payload(TamagotchiFactoryEvent::IdOf(Some(first_id)));
+    assert!(res.contains(&log));
+    let res = army.send(USER, TamagotchiFactoryAction::IdOf(ActorId::zero()));
+    let log = Log::builder()
+        .dest(USER)
+        .payload(TamagotchiFactoryEvent::IdOf(None));
+    assert!(res.contains(&log));
+
+    let res = army.send(
+        USER,
+        TamagotchiFactoryAction::All {
+            offset: 2,
+            limit: 1,
+        },
+    );
+    let log = Log::builder()
+        .dest(USER)
+        .payload(TamagotchiFactoryEvent::All(vec![(
+            second_id,
+            second_address,
+        )]));
+    assert!(res.contains(&log));
+}
+
+#[test]
+fn transfer_moves_the_tamagotchi_between_owners() {
+    let sys = System::new();
+    sys.init_logger();
+    let army = init_army(&sys);
+    let (tamagotchi_id, _) = create_tamagotchi(&army, OWNER);
+
+    let res = army.send(
+        OWNER,
+        TamagotchiFactoryAction::TransferTamagotchi {
+            tamagotchi_id,
+            new_owner: USER.into(),
+        },
+    );
+    assert!(!res.main_failed());
+    assert!(tamagotchis_of(&army, OWNER, 0, 10).is_empty());
+    assert_eq!(tamagotchis_of(&army, USER, 0, 10), [tamagotchi_id]);
+
+    // an owner without Tamagotchis is dropped from the index
+    let state: TamagotchiFactory = army.read_state(b"").expect("Unable to read the state");
+    assert!(!state.owner_to_ids.contains_key(&OWNER.into()));
+    assert_eq!(state.id_to_owner[&tamagotchi_id], USER.into());
+}
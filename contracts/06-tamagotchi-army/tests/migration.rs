@@ -0,0 +1,70 @@
+mod common;
+
+use common::{create_tamagotchi, events, init_army, ADMIN, OWNER, TAMAGOTCHI_WASM};
+use gstd::{ActorId, CodeId};
+use gtest::{Program, System};
+use tamagotchi_army_io::{TamagotchiFactoryAction, TamagotchiFactoryEvent};
+use tamagotchi_auto_io::{Tamagotchi, TmgAction};
+
+const POOL: u128 = 1_000_000_000_000;
+
+fn tamagotchi_state(sys: &System, address: ActorId) -> Tamagotchi {
+    sys.get_program(<[u8; 32]>::from(address))
+        .read_state(b"")
+        .expect("Unable to read the state")
+}
+
+fn set_tamagotchi_code(army: &Program<'_>, code_id: CodeId, version: u32) {
+    let res = army.send(
+        ADMIN,
+        TamagotchiFactoryAction::SetTamagotchiCode { code_id, version },
+    );
+    assert!(!res.main_failed());
+}
+
+#[test]
+fn failed_migration_can_be_retried() {
+    let sys = System::new();
+    sys.init_logger();
+    let army = init_army(&sys);
+    let (tamagotchi_id, old_address) = create_tamagotchi(&army, OWNER);
+
+    let old_tamagotchi = sys.get_program(<[u8; 32]>::from(old_address));
+    sys.mint_to(OWNER, POOL * 2);
+    let res =
+        old_tamagotchi.send_with_value(OWNER, TmgAction::FundKeeperPool { ft_amount: 0 }, POOL);
+    assert!(!res.main_failed());
+
+    // the new program can't be created from a code that doesn't exist, the
+    // migration fails after the old program exported its state
+    set_tamagotchi_code(&army, CodeId::from([1; 32]), 2);
+    let res = army.send(OWNER, TamagotchiFactoryAction::Migrate(tamagotchi_id));
+    assert!(res.main_failed());
+    assert!(tamagotchi_state(&sys, old_address).exported);
+
+    // the exported program rejects everything but the export
+    let res = old_tamagotchi.send(OWNER, TmgAction::Feed);
+    assert!(res.main_failed());
+
+    let code_id = sys.submit_code(TAMAGOTCHI_WASM);
+    set_tamagotchi_code(&army, CodeId::from(code_id.into_bytes()), 3);
+    let res = army.send(OWNER, TamagotchiFactoryAction::Migrate(tamagotchi_id));
+    let new_address = events(&res, OWNER)
+        .into_iter()
+        .find_map(|event| match event {
+            TamagotchiFactoryEvent::Migrated {
+                tamagotchi_address,
+                version: 3,
+                ..
+            } => Some(tamagotchi_address),
+            _ => None,
+        })
+        .expect("The Tamagotchi wasn't migrated");
+    assert_ne!(new_address, old_address);
+
+    // the keeper pool moved along with the state
+    let state = tamagotchi_state(&sys, new_address);
+    assert_eq!(state.owner, OWNER.into());
+    assert!(!state.exported);
+    assert_eq!(state.keeper_pool.native, POOL);
+}
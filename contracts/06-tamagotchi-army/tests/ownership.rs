@@ -0,0 +1,44 @@
+mod common;
+
+use common::{create_tamagotchi, init_army, OWNER, USER};
+use gstd::ActorId;
+use gtest::{Log, System};
+use tamagotchi_army_io::{TamagotchiFactoryAction, TamagotchiFactoryEvent};
+use tamagotchi_auto_io::Tamagotchi;
+
+#[test]
+fn created_tamagotchi_belongs_to_its_creator() {
+    let sys = System::new();
+    sys.init_logger();
+    let army = init_army(&sys);
+    let (tamagotchi_id, tamagotchi_address) = create_tamagotchi(&army, OWNER);
+
+    let tamagotchi = sys.get_program(<[u8; 32]>::from(tamagotchi_address));
+    let state: Tamagotchi = tamagotchi
+        .read_state(b"")
+        .expect("Unable to read the state");
+    assert_eq!(state.owner, OWNER.into());
+    assert_eq!(state.factory, Some(ActorId::new(army.id().into_bytes())));
+
+    // the factory forwards the caller, who isn't the owner
+    let res = army.send(
+        USER,
+        TamagotchiFactoryAction::TransferTamagotchi {
+            tamagotchi_id,
+            new_owner: USER.into(),
+        },
+    );
+    assert!(res.main_failed());
+
+    let res = army.send(
+        OWNER,
+        TamagotchiFactoryAction::TransferTamagotchi {
+            tamagotchi_id,
+            new_owner: USER.into(),
+        },
+    );
+    let log = Log::builder()
+        .dest(OWNER)
+        .payload(TamagotchiFactoryEvent::Transferred(USER.into()));
+    assert!(res.contains(&log));
+}
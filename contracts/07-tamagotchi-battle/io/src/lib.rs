@@ -1,10 +1,11 @@
 #![no_std]
 
+use access_control::{AccessControl, Role};
 use gmeta::{Metadata, InOut, Out};
 use gstd::{
     prelude::*,msg,ActorId,collections::{BTreeSet, BTreeMap},exec,ReservationId
 };
-use tamagotchi_auto_io::{TmgAction, TmgEvent};
+use tamagotchi_auto_io::{GasReservation, ReservationManager, TmgAction, TmgEvent};
 use tamagotchi_store_io::{StoreEvent, StoreAction, AttributeId};
 use tamagotchi_store_io::TamagotchiId;
 
@@ -49,6 +50,22 @@ pub enum BattleAction {
         reservation_amount: u64,
         duration: u32,
     },
+    GrantRole {
+        role: Role,
+        account: ActorId,
+    },
+    RevokeRole {
+        role: Role,
+        account: ActorId,
+    },
+    TransferOwnership {
+        new_owner: ActorId,
+    },
+    AcceptOwnership,
+    Pause,
+    Unpause,
+    Unreserve(ReservationId),
+    Reservations,
 }
 
 #[derive(Encode, Decode, TypeInfo, Default)]
@@ -66,7 +83,27 @@ pub enum BattleEvent {
     AttributesUpdated,
     ContractReinstated,
     GasReserved,
-    OpponentDodgedTheAttack
+    OpponentDodgedTheAttack,
+    RoleGranted {
+        role: Role,
+        account: ActorId
+    },
+    RoleRevoked {
+        role: Role,
+        account: ActorId
+    },
+    OwnershipTransferStarted {
+        new_owner: ActorId
+    },
+    OwnershipTransferred {
+        new_owner: ActorId
+    },
+    Paused,
+    Unpaused,
+    Unreserved {
+        amount: u64
+    },
+    Reservations(Vec<GasReservation>),
 }
 
 #[derive(Encode, Decode, TypeInfo, Default, Clone)]
@@ -108,7 +145,9 @@ pub struct Battle {
     pub steps: u8,
     pub weapons_data: BTreeMap<AttributeId, PowerTmg>,
     pub shields_data: BTreeMap<AttributeId, ProtectionTmg>,
-    pub reservations: Vec<ReservationId>,
+    pub reservations: ReservationManager,
+    pub access_control: AccessControl,
+    pub paused: bool,
 }
 
 
@@ -122,7 +161,7 @@ const MAX_POWER: u16 = 5_000;
 const MIN_POWER: u16 = 3_000;
 const MAX_ENERGY: u16 = 10_000;
 const MIN_ENERGY: u16 = 20_000;
-const GAS_AMOUNT: u64 = 10_000_000_000;
+pub const GAS_AMOUNT: u64 = 10_000_000_000;
 const TIME_FOR_UPDATE: u32 = 10;
 const MAX_STEPS_FOR_ROUND: u8 = 4;
 
@@ -150,6 +189,7 @@ impl Battle {
             BattleState::Registration,
             "The game has already started"
         );
+        assert!(!self.paused, "Registration is paused");
     
         let owner = Self::fetch_owner(tmg_id).await;
         let power = Self::generate_random_number_field(MIN_POWER, MAX_POWER);
@@ -299,11 +339,7 @@ impl Battle {
             self.players[turn].actual_attribute = 1;
             self.players[next_turn].actual_attribute = 1;
             
-            if !self.reservations.is_empty() {
-                let Some(reservation_id) = self.reservations.pop() else {
-                    panic!("Error getting transaction id, reservations is empty");  
-                };
-                
+            if let Some(reservation_id) = self.reservations.take() {
                 msg::send_delayed_from_reservation(
                     reservation_id,
                     exec::program_id(),
@@ -418,17 +454,27 @@ impl Battle {
     
     
     pub fn initiate_reservation(&mut self, reservation_amount: u64, reservation_duration: u32) {
-        let reservation_id = ReservationId::reserve(
-            reservation_amount,
-            reservation_duration
-        ).expect("reservation across executions");
-        
-        self.reservations.push(reservation_id);
+        // reservations are paid from the program balance
+        self.access_control.check_role(Role::Treasurer);
+        self.reservations.reserve(reservation_amount, reservation_duration);
         
         msg::reply(BattleEvent::GasReserved, 0)
             .expect("Error in reply");
     }
+    
+    pub fn unreserve(&mut self, reservation_id: ReservationId) {
+        self.access_control.check_role(Role::Owner);
+        let amount = self.reservations.unreserve(reservation_id);
+        msg::reply(BattleEvent::Unreserved { amount }, 0)
+            .expect("Error in sending a reply `BattleEvent::Unreserved`");
+    }
+    
+    pub fn reservations(&self) {
+        msg::reply(BattleEvent::Reservations(self.reservations.active()), 0)
+            .expect("Error in sending a reply `BattleEvent::Reservations`");
+    }
     pub fn reset_game(&mut self) {
+        self.access_control.check_role(Role::Owner);
         assert_eq!(
             self.state,
             BattleState::GameIsOver,
@@ -440,6 +486,46 @@ impl Battle {
         msg::reply(BattleEvent::ContractReinstated, 0)
             .expect("Error sending reply");
     }
+    
+    pub fn grant_role(&mut self, role: Role, account: ActorId) {
+        self.access_control.grant_role(role, account);
+        msg::reply(BattleEvent::RoleGranted { role, account }, 0)
+            .expect("Error in sending a reply `BattleEvent::RoleGranted`");
+    }
+    
+    pub fn revoke_role(&mut self, role: Role, account: ActorId) {
+        self.access_control.revoke_role(role, account);
+        msg::reply(BattleEvent::RoleRevoked { role, account }, 0)
+            .expect("Error in sending a reply `BattleEvent::RoleRevoked`");
+    }
+    
+    pub fn transfer_ownership(&mut self, new_owner: ActorId) {
+        self.access_control.transfer_ownership(new_owner);
+        msg::reply(BattleEvent::OwnershipTransferStarted { new_owner }, 0)
+            .expect("Error in sending a reply `BattleEvent::OwnershipTransferStarted`");
+    }
+    
+    pub fn accept_ownership(&mut self) {
+        self.access_control.accept_ownership();
+        msg::reply(
+            BattleEvent::OwnershipTransferred {
+                new_owner: self.access_control.owner,
+            },
+            0,
+        )
+        .expect("Error in sending a reply `BattleEvent::OwnershipTransferred`");
+    }
+    
+    pub fn set_paused(&mut self, paused: bool) {
+        self.access_control.check_role(Role::Pauser);
+        self.paused = paused;
+        let event = if paused {
+            BattleEvent::Paused
+        } else {
+            BattleEvent::Unpaused
+        };
+        msg::reply(event, 0).expect("Error in sending a reply `BattleEvent::Paused`");
+    }
 }
 
 
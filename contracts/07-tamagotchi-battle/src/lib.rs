@@ -1,4 +1,5 @@
 #![no_std]
+use access_control::AccessControl;
 use gstd::{msg, prelude::*, collections::BTreeMap};
 use tamagotchi_battle_io::*;
 
@@ -19,6 +20,7 @@ extern "C" fn init() {
         shields_data: BTreeMap::from([
             (SHIELD_ID, SHIELD),
         ]),
+        access_control: AccessControl::new(msg::source()),
         ..Default::default()
     };
     unsafe { TAMAGOTCHI_BATTLE = Some(tamagotchi_battle) };
@@ -58,6 +60,30 @@ async fn main() {
             duration 
         } => {
             tmg_battle.initiate_reservation(reservation_amount, duration);
+        },
+        BattleAction::Unreserve(reservation_id) => {
+            tmg_battle.unreserve(reservation_id);
+        },
+        BattleAction::Reservations => {
+            tmg_battle.reservations();
+        },
+        BattleAction::GrantRole { role, account } => {
+            tmg_battle.grant_role(role, account);
+        },
+        BattleAction::RevokeRole { role, account } => {
+            tmg_battle.revoke_role(role, account);
+        },
+        BattleAction::TransferOwnership { new_owner } => {
+            tmg_battle.transfer_ownership(new_owner);
+        },
+        BattleAction::AcceptOwnership => {
+            tmg_battle.accept_ownership();
+        },
+        BattleAction::Pause => {
+            tmg_battle.set_paused(true);
+        },
+        BattleAction::Unpause => {
+            tmg_battle.set_paused(false);
         }
     }
 }
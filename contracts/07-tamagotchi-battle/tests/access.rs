@@ -0,0 +1,128 @@
+use access_control::Role;
+use gstd::{codec::Decode, ActorId};
+use gtest::{Log, Program, System};
+use tamagotchi_battle_io::{BattleAction, BattleEvent, BattleInit};
+
+const OWNER: u64 = 100;
+const USER: u64 = 101;
+const RESERVATION_AMOUNT: u64 = 1_000_000_000;
+const RESERVATION_DURATION: u32 = 1_000;
+
+fn init_battle(sys: &System) -> Program<'_> {
+    let battle = Program::current(sys);
+    let res = battle.send(
+        OWNER,
+        BattleInit {
+            tmg_store_id: ActorId::zero(),
+        },
+    );
+    assert!(!res.main_failed());
+
+    battle
+}
+
+fn reserve_gas() -> BattleAction {
+    BattleAction::ReserveGas {
+        reservation_amount: RESERVATION_AMOUNT,
+        duration: RESERVATION_DURATION,
+    }
+}
+
+#[test]
+fn only_treasurer_reserves_gas() {
+    let sys = System::new();
+    sys.init_logger();
+    let battle = init_battle(&sys);
+
+    let res = battle.send(USER, reserve_gas());
+    assert!(res.main_failed());
+
+    let res = battle.send(OWNER, reserve_gas());
+    let log = Log::builder().dest(OWNER).payload(BattleEvent::GasReserved);
+    assert!(res.contains(&log));
+
+    // must fail since only the owner grants roles
+    let res = battle.send(
+        USER,
+        BattleAction::GrantRole {
+            role: Role::Treasurer,
+            account: USER.into(),
+        },
+    );
+    assert!(res.main_failed());
+
+    let res = battle.send(
+        OWNER,
+        BattleAction::GrantRole {
+            role: Role::Treasurer,
+            account: USER.into(),
+        },
+    );
+    assert!(!res.main_failed());
+
+    let res = battle.send(USER, reserve_gas());
+    let log = Log::builder().dest(USER).payload(BattleEvent::GasReserved);
+    assert!(res.contains(&log));
+
+    let res = battle.send(
+        OWNER,
+        BattleAction::RevokeRole {
+            role: Role::Treasurer,
+            account: USER.into(),
+        },
+    );
+    assert!(!res.main_failed());
+
+    let res = battle.send(USER, reserve_gas());
+    assert!(res.main_failed());
+}
+
+#[test]
+fn unauthorized_callers_are_rejected() {
+    let sys = System::new();
+    sys.init_logger();
+    let battle = init_battle(&sys);
+
+    // pausing doesn't depend on the game state, only on the caller's role
+    let res = battle.send(USER, BattleAction::Pause);
+    assert!(res.main_failed());
+    let res = battle.send(OWNER, BattleAction::Pause);
+    let log = Log::builder().dest(OWNER).payload(BattleEvent::Paused);
+    assert!(res.contains(&log));
+
+    let res = battle.send(USER, BattleAction::Unpause);
+    assert!(res.main_failed());
+    let res = battle.send(
+        OWNER,
+        BattleAction::GrantRole {
+            role: Role::Pauser,
+            account: USER.into(),
+        },
+    );
+    assert!(!res.main_failed());
+    let res = battle.send(USER, BattleAction::Unpause);
+    let log = Log::builder().dest(USER).payload(BattleEvent::Unpaused);
+    assert!(res.contains(&log));
+
+    let res = battle.send(OWNER, reserve_gas());
+    assert!(!res.main_failed());
+    let res = battle.send(OWNER, BattleAction::Reservations);
+    let reservations = res
+        .log()
+        .iter()
+        .find_map(|log| match BattleEvent::decode(&mut log.payload()) {
+            Ok(BattleEvent::Reservations(reservations)) => Some(reservations),
+            _ => None,
+        })
+        .expect("No `BattleEvent::Reservations` in the reply");
+    assert_eq!(reservations.len(), 1);
+
+    let res = battle.send(USER, BattleAction::Unreserve(reservations[0].id));
+    assert!(res.main_failed());
+
+    let res = battle.send(OWNER, BattleAction::Unreserve(reservations[0].id));
+    let log = Log::builder().dest(OWNER).payload(BattleEvent::Unreserved {
+        amount: RESERVATION_AMOUNT,
+    });
+    assert!(res.contains(&log));
+}
@@ -1,5 +1,6 @@
 #![no_std]
 
+use access_control::{AccessControl, Role};
 use gmeta::{In, InOut, Metadata as GMetadata, Out};
 use sharded_fungible_token_io::{FTokenAction, FTokenEvent, LogicAction};
 
@@ -19,7 +20,8 @@ pub type TransactionId = u64;
 #[codec(crate = gstd::codec)]
 #[scale_info(crate = gstd::scale_info)]
 pub struct AttributeStore {
-    pub admin: ActorId,
+    pub access_control: AccessControl,
+    pub paused: bool,
     pub ft_contract_id: ActorId,    
     pub cost_to_upgrade_weapons: Price,
     pub attributes: BTreeMap<AttributeId, (AttrMetadata, Price)>,
@@ -73,7 +75,20 @@ pub enum StoreAction {
     RemoveTx {
         tamagotchi_id: TamagotchiId,
     },
-    
+    GrantRole {
+        role: Role,
+        account: ActorId,
+    },
+    RevokeRole {
+        role: Role,
+        account: ActorId,
+    },
+    TransferOwnership {
+        new_owner: ActorId,
+    },
+    AcceptOwnership,
+    Pause,
+    Unpause,
 }
 
 #[derive(Encode, Decode, TypeInfo)]
@@ -88,7 +103,13 @@ pub enum StoreEvent {
     FtContractIdSet { ft_contract_id: ActorId },
     TxRemoved { tamagotchi_id: ActorId },
     BuyTheAttributeToUpdateIt,
-    AttributeCannotBeImproved
+    AttributeCannotBeImproved,
+    RoleGranted { role: Role, account: ActorId },
+    RoleRevoked { role: Role, account: ActorId },
+    OwnershipTransferStarted { new_owner: ActorId },
+    OwnershipTransferred { new_owner: ActorId },
+    Paused,
+    Unpaused,
 }
 
 impl AttributeStore {
@@ -100,7 +121,7 @@ impl AttributeStore {
         can_upgrade: bool,
         price: Price,
     ) {
-        assert_eq!(msg::source(), self.admin, "Only admin can add attributes");
+        self.access_control.check_role(Role::CatalogManager);
 
         if self
             .attributes
@@ -119,6 +140,8 @@ impl AttributeStore {
     }
     
     pub async fn purchase_attribute(&mut self, attribute_id: AttributeId) {
+        assert!(!self.paused, "The store is paused");
+
         let (transaction_id, attribute_id) = if let Some((transaction_id, prev_attribute_id)) =
             self.transactions.get(&msg::source())
         {
@@ -180,6 +203,7 @@ impl AttributeStore {
     }
     
     pub async fn upgrade_attribute(&mut self, attribute_id: AttributeId) {
+        assert!(!self.paused, "The store is paused");
         let caller = msg::source();
         
         let Some(&upgrade_id) = self.improvable_attributes.get(&attribute_id) else {
@@ -275,11 +299,7 @@ impl AttributeStore {
     }
 
     pub fn set_ft_contract_id(&mut self, ft_contract_id: &ActorId) {
-        assert_eq!(
-            msg::source(),
-            self.admin,
-            "Only admin can set fungible token contract"
-        );
+        self.access_control.check_role(Role::Owner);
         self.ft_contract_id = *ft_contract_id;
         msg::reply(
             StoreEvent::FtContractIdSet {
@@ -291,11 +311,7 @@ impl AttributeStore {
     }
 
     pub fn remove_transaction(&mut self, tmg_id: &TamagotchiId) {
-        assert_eq!(
-            msg::source(),
-            self.admin,
-            "Only admin can set remove transactions"
-        );
+        self.access_control.check_role(Role::Owner);
         self.transactions.remove(tmg_id);
         msg::reply(
             StoreEvent::TxRemoved {
@@ -305,6 +321,46 @@ impl AttributeStore {
         )
         .expect("Error in sending a reply `StoreEvent::TxRemoved`");
     }
+
+    pub fn grant_role(&mut self, role: Role, account: ActorId) {
+        self.access_control.grant_role(role, account);
+        msg::reply(StoreEvent::RoleGranted { role, account }, 0)
+            .expect("Error in sending a reply `StoreEvent::RoleGranted`");
+    }
+
+    pub fn revoke_role(&mut self, role: Role, account: ActorId) {
+        self.access_control.revoke_role(role, account);
+        msg::reply(StoreEvent::RoleRevoked { role, account }, 0)
+            .expect("Error in sending a reply `StoreEvent::RoleRevoked`");
+    }
+
+    pub fn transfer_ownership(&mut self, new_owner: ActorId) {
+        self.access_control.transfer_ownership(new_owner);
+        msg::reply(StoreEvent::OwnershipTransferStarted { new_owner }, 0)
+            .expect("Error in sending a reply `StoreEvent::OwnershipTransferStarted`");
+    }
+
+    pub fn accept_ownership(&mut self) {
+        self.access_control.accept_ownership();
+        msg::reply(
+            StoreEvent::OwnershipTransferred {
+                new_owner: self.access_control.owner,
+            },
+            0,
+        )
+        .expect("Error in sending a reply `StoreEvent::OwnershipTransferred`");
+    }
+
+    pub fn set_paused(&mut self, paused: bool) {
+        self.access_control.check_role(Role::Pauser);
+        self.paused = paused;
+        let event = if paused {
+            StoreEvent::Paused
+        } else {
+            StoreEvent::Unpaused
+        };
+        msg::reply(event, 0).expect("Error in sending a reply `StoreEvent::Paused`");
+    }
 }
 
 pub async fn transfer_tokens(
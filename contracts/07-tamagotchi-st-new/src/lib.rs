@@ -1,5 +1,6 @@
 #![no_std]
 
+use access_control::AccessControl;
 use gstd::{collections::{BTreeMap, BTreeSet},exec, msg,prelude::*,ActorId,
 };
 use sharded_fungible_token_io::{FTokenAction, FTokenEvent, LogicAction};
@@ -11,7 +12,7 @@ static mut STORE: Option<AttributeStore> = None;
 extern fn init() {
     let StoreInit {ft_contract_id, cost_to_upgrade_weapons} = msg::load().expect("Unable to decode `ActorId`");
     let store = AttributeStore {
-        admin: msg::source(),
+        access_control: AccessControl::new(msg::source()),
         ft_contract_id,
         cost_to_upgrade_weapons,
         ..Default::default()
@@ -44,6 +45,12 @@ async fn main() {
             // store.update_attributes(attribute_id);
             store.upgrade_attribute(attribute_id).await;
         }
+        StoreAction::GrantRole { role, account } => store.grant_role(role, account),
+        StoreAction::RevokeRole { role, account } => store.revoke_role(role, account),
+        StoreAction::TransferOwnership { new_owner } => store.transfer_ownership(new_owner),
+        StoreAction::AcceptOwnership => store.accept_ownership(),
+        StoreAction::Pause => store.set_paused(true),
+        StoreAction::Unpause => store.set_paused(false),
     }
 }
 
@@ -53,7 +60,8 @@ extern fn state() {
 
     msg::reply(
         AttributeStore {
-            admin: store.admin,
+            access_control: store.access_control.clone(),
+            paused: store.paused,
             ft_contract_id: store.ft_contract_id,
             attributes: store.attributes.clone(),
             owners: store.owners.clone(),
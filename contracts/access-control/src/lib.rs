@@ -0,0 +1,94 @@
+#![no_std]
+
+use gstd::{
+    collections::{BTreeMap, BTreeSet},
+    msg,
+    prelude::*,
+    ActorId,
+};
+
+#[derive(Encode, Decode, TypeInfo, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub enum Role {
+    Owner,
+    CatalogManager,
+    Treasurer,
+    Pauser,
+}
+
+/// Roles of a program's administrators.
+///
+/// The owner implicitly holds every role. Ownership can't be granted, it's
+/// handed over in two steps: the owner proposes a new owner with
+/// [`AccessControl::transfer_ownership`] and the proposed account confirms it
+/// with [`AccessControl::accept_ownership`].
+#[derive(Default, Encode, Decode, TypeInfo, Clone, Debug)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub struct AccessControl {
+    pub owner: ActorId,
+    pub pending_owner: Option<ActorId>,
+    pub roles: BTreeMap<ActorId, BTreeSet<Role>>,
+}
+
+impl AccessControl {
+    pub fn new(owner: ActorId) -> Self {
+        Self {
+            owner,
+            ..Default::default()
+        }
+    }
+
+    pub fn has_role(&self, account: &ActorId, role: Role) -> bool {
+        if self.owner == *account {
+            return true;
+        }
+        self.roles
+            .get(account)
+            .is_some_and(|roles| roles.contains(&role))
+    }
+
+    pub fn check_role(&self, role: Role) {
+        assert!(
+            self.has_role(&msg::source(), role),
+            "The caller doesn't have the {role:?} role"
+        );
+    }
+
+    pub fn grant_role(&mut self, role: Role, account: ActorId) {
+        self.check_role(Role::Owner);
+        assert_ne!(role, Role::Owner, "Ownership can only be transferred");
+
+        self.roles.entry(account).or_default().insert(role);
+    }
+
+    pub fn revoke_role(&mut self, role: Role, account: ActorId) {
+        self.check_role(Role::Owner);
+
+        if let Some(roles) = self.roles.get_mut(&account) {
+            roles.remove(&role);
+            if roles.is_empty() {
+                self.roles.remove(&account);
+            }
+        }
+    }
+
+    pub fn transfer_ownership(&mut self, new_owner: ActorId) {
+        self.check_role(Role::Owner);
+
+        self.pending_owner = Some(new_owner);
+    }
+
+    pub fn accept_ownership(&mut self) {
+        let caller = msg::source();
+        assert_eq!(
+            self.pending_owner,
+            Some(caller),
+            "The caller isn't the pending owner"
+        );
+
+        self.owner = caller;
+        self.pending_owner = None;
+    }
+}
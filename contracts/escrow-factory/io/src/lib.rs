@@ -5,7 +5,7 @@ use gstd::{collections::BTreeMap, msg, prelude::*, prog::ProgramGenerator, Actor
 
 pub type EscrowId = u64;
 
-const GAS_FOR_CREATION: u64 = 1_000_000_000;
+pub const GAS_FOR_CREATION: u64 = 1_000_000_000;
 
 pub struct ProgramMetadata;
 